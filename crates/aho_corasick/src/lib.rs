@@ -0,0 +1,242 @@
+const NONE: usize = !0;
+
+/// 文字cは c - base として [0, sigma) に写します。
+/// bytesをそのまま使うなら AhoCorasick::new(256, 0) です。
+pub struct AhoCorasick {
+    sigma: usize,
+    base: usize,
+    next: Vec<usize>,
+    fail: Vec<usize>,
+    output: Vec<usize>,
+    count: Vec<usize>,
+    ids: Vec<Vec<usize>>,
+    pattern_len: Vec<usize>,
+    order: Vec<usize>,
+}
+impl AhoCorasick {
+    pub fn new(sigma: usize, base: usize) -> Self {
+        Self {
+            sigma,
+            base,
+            next: vec![NONE; sigma],
+            fail: vec![0],
+            output: vec![NONE],
+            count: vec![0],
+            ids: vec![vec![]],
+            pattern_len: vec![],
+            order: vec![],
+        }
+    }
+    #[inline]
+    fn symbol<T: Into<usize>>(&self, c: T) -> usize {
+        let c = c.into();
+        assert!(self.base <= c && c - self.base < self.sigma);
+        c - self.base
+    }
+    pub fn insert<T: Into<usize> + Copy>(&mut self, pattern: &[T]) -> usize {
+        //! return: pattern id
+        assert!(self.order.is_empty(), "already built");
+        let mut now = 0;
+        for &c in pattern {
+            let c = self.symbol(c);
+            if self.next[now * self.sigma + c] == NONE {
+                self.next[now * self.sigma + c] = self.fail.len();
+                self.next.extend((0..self.sigma).map(|_| NONE));
+                self.fail.push(0);
+                self.output.push(NONE);
+                self.count.push(0);
+                self.ids.push(vec![]);
+            }
+            now = self.next[now * self.sigma + c];
+        }
+        let id = self.pattern_len.len();
+        self.pattern_len.push(pattern.len());
+        self.ids[now].push(id);
+        self.count[now] += 1;
+        id
+    }
+    pub fn build(&mut self) {
+        //! 全部insertしてから呼んでください。goto表が完成します。
+        let sigma = self.sigma;
+        self.order.push(0);
+        for c in 0..sigma {
+            let v = self.next[c];
+            if v == NONE {
+                self.next[c] = 0;
+            } else {
+                self.fail[v] = 0;
+                self.order.push(v);
+            }
+        }
+        let mut head = 1;
+        while head < self.order.len() {
+            let u = self.order[head];
+            head += 1;
+            let f = self.fail[u];
+            self.output[u] = if self.ids[f].is_empty() {
+                self.output[f]
+            } else {
+                f
+            };
+            self.count[u] += self.count[f];
+            for c in 0..sigma {
+                let v = self.next[u * sigma + c];
+                if v == NONE {
+                    self.next[u * sigma + c] = self.next[f * sigma + c];
+                } else {
+                    self.fail[v] = self.next[f * sigma + c];
+                    self.order.push(v);
+                }
+            }
+        }
+    }
+    pub fn size(&self) -> usize {
+        //! 状態数
+        self.fail.len()
+    }
+    pub fn sigma(&self) -> usize {
+        self.sigma
+    }
+    pub fn root(&self) -> usize {
+        0
+    }
+    pub fn goto<T: Into<usize>>(&self, state: usize, c: T) -> usize {
+        self.transition(state, self.symbol(c))
+    }
+    pub fn transition(&self, state: usize, i: usize) -> usize {
+        //! i番目の文字 (c - base = i) での遷移先です。DP用。
+        debug_assert!(!self.order.is_empty(), "not built");
+        self.next[state * self.sigma + i]
+    }
+    pub fn fail(&self, state: usize) -> usize {
+        self.fail[state]
+    }
+    pub fn output_link(&self, state: usize) -> Option<usize> {
+        //! failを辿って最初に見つかる、パターンが終わる状態
+        match self.output[state] {
+            NONE => None,
+            x => Some(x),
+        }
+    }
+    pub fn count(&self, state: usize) -> usize {
+        //! この状態の文字列の接尾辞になっているパターンの数 (重複込み)
+        self.count[state]
+    }
+    pub fn ids(&self, state: usize) -> &[usize] {
+        //! ちょうどこの状態で終わるパターンのid
+        &self.ids[state]
+    }
+    pub fn matches<T: Into<usize> + Copy>(&self, text: &[T]) -> Vec<(usize, usize)> {
+        //! return: (開始位置, pattern id) の列
+        let mut ret = Vec::new();
+        let mut now = 0;
+        for (i, &c) in text.iter().enumerate() {
+            now = self.goto(now, c);
+            let mut v = if self.ids[now].is_empty() {
+                self.output[now]
+            } else {
+                now
+            };
+            while v != NONE {
+                for &id in &self.ids[v] {
+                    ret.push((i + 1 - self.pattern_len[id], id));
+                }
+                v = self.output[v];
+            }
+        }
+        ret
+    }
+    pub fn count_each<T: Into<usize> + Copy>(&self, text: &[T]) -> Vec<usize> {
+        //! パターンごとの出現回数。O(|text| + 状態数)
+        let mut visit = vec![0; self.size()];
+        let mut now = 0;
+        for &c in text {
+            now = self.goto(now, c);
+            visit[now] += 1;
+        }
+        for &u in self.order.iter().skip(1).rev() {
+            visit[self.fail[u]] += visit[u];
+        }
+        let mut ret = vec![0; self.pattern_len.len()];
+        for (u, ids) in self.ids.iter().enumerate() {
+            for &id in ids {
+                ret[id] = visit[u];
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use static_modint::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn matches_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let mut ac = AhoCorasick::new(3, b'a' as usize);
+            let patterns: Vec<Vec<u8>> = (0..5)
+                .map(|_| {
+                    let len = 1 + rng.gen() as usize % 4;
+                    (0..len).map(|_| b'a' + (rng.gen() % 3) as u8).collect()
+                })
+                .collect();
+            for p in &patterns {
+                ac.insert(p);
+            }
+            ac.build();
+            let text: Vec<u8> = (0..100).map(|_| b'a' + (rng.gen() % 3) as u8).collect();
+            let mut naive = Vec::new();
+            let mut each = vec![0; patterns.len()];
+            for end in 1..=text.len() {
+                for (id, p) in patterns.iter().enumerate() {
+                    if end >= p.len() && text[end - p.len()..end] == p[..] {
+                        naive.push((end - p.len(), id));
+                        each[id] += 1;
+                    }
+                }
+            }
+            let mut got = ac.matches(&text);
+            got.sort_by_key(|&(l, id)| (l + patterns[id].len(), id));
+            assert_eq!(got, naive);
+            assert_eq!(ac.count_each(&text), each);
+        }
+    }
+    #[test]
+    fn avoid_patterns_dp_test() {
+        type ModInt = Modint<P998244353>;
+        let patterns: [&[u8]; 3] = [b"aba", b"bb", b"aaaa"];
+        let mut ac = AhoCorasick::new(2, b'a' as usize);
+        for p in patterns.iter() {
+            ac.insert(p);
+        }
+        ac.build();
+        let n = 12;
+        let mut dp = vec![ModInt::new(0); ac.size()];
+        dp[ac.root()] = ModInt::new(1);
+        for _ in 0..n {
+            let mut next = vec![ModInt::new(0); ac.size()];
+            for (s, &x) in dp.iter().enumerate() {
+                for c in 0..ac.sigma() {
+                    let t = ac.transition(s, c);
+                    if ac.count(t) == 0 {
+                        next[t] += x;
+                    }
+                }
+            }
+            dp = next;
+        }
+        let ans = dp.iter().fold(ModInt::new(0), |acc, &x| acc + x);
+        let naive = (0..1u32 << n)
+            .filter(|&bits| {
+                let s: Vec<u8> = (0..n).map(|i| b'a' + (bits >> i & 1) as u8).collect();
+                patterns
+                    .iter()
+                    .all(|p| !s.windows(p.len()).any(|w| w == *p))
+            })
+            .count();
+        assert_eq!(ans.value(), naive as u64);
+    }
+}
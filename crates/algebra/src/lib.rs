@@ -50,6 +50,26 @@ impl<T: Monoid + Loop> Group for T {}
 
 pub trait Abel: Group {}
 
+/// Tへの作用。積に作用させたものと、作用させたものの積が同じになるようにしてください。
+pub trait Act<T>: Monoid {
+    fn act(&self, x: &T) -> T;
+}
+
+impl Magma for () {
+    fn op<L: Into<Self>, R: Into<Self>>(_: L, _: R) -> Self {}
+}
+impl Semigroup for () {}
+impl Identity for () {
+    fn identity() -> Self {}
+}
+impl Commut for () {}
+/// 何もしない作用
+impl<T: Clone> Act<T> for () {
+    fn act(&self, x: &T) -> T {
+        x.clone()
+    }
+}
+
 
 #[macro_export]
 macro_rules! def_monoid {
@@ -168,6 +188,7 @@ macro_rules! impl_abel_integer {
                 -self
             }
         }
+        impl Commut for $t {}
         impl Abel for $t {}
     };
     ($($t:ty),+) => {
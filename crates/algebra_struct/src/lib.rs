@@ -15,3 +15,44 @@ def_monoid! {
         MaxMonoid(lhs.0.max(rhs.0))
     }
 }
+// 端の値と和。列の順番や向きと、区間への加算を確かめるのに使います。
+def_monoid! {
+    derive(Copy),
+    pub struct Ends {
+        pub first: Option<i64>,
+        pub last: Option<i64>,
+        pub sum: i64,
+        pub len: i64,
+    },
+    Ends { first: None, last: None, sum: 0, len: 0 },
+    fn op(lhs: Ends, rhs: Ends) -> Ends {
+        Ends {
+            first: lhs.first.or(rhs.first),
+            last: rhs.last.or(lhs.last),
+            sum: lhs.sum + rhs.sum,
+            len: lhs.len + rhs.len,
+        }
+    }
+}
+impl Ends {
+    pub fn leaf(x: i64) -> Self {
+        //! 1要素だけのもの
+        Ends {
+            first: Some(x),
+            last: Some(x),
+            sum: x,
+            len: 1,
+        }
+    }
+}
+/// 全部にxを足します。
+impl Act<Ends> for i64 {
+    fn act(&self, x: &Ends) -> Ends {
+        Ends {
+            first: x.first.map(|y| y + self),
+            last: x.last.map(|y| y + self),
+            sum: x.sum + self * x.len,
+            len: x.len,
+        }
+    }
+}
@@ -1,187 +1,243 @@
+mod persistent;
+pub use persistent::*;
+
+const NONE: usize = !0;
+
 struct Node {
     size: usize,
-    child: [Tree; 2],
+    child: [usize; 2],
 }
 impl Node {
     fn none() -> Self {
         Self {
             size: 0,
-            child: [Tree::none(), Tree::none()],
+            child: [NONE; 2],
         }
     }
 }
-struct Tree(Option<Box<Node>>);
-impl Tree {
-    fn none() -> Self {
-        Tree(None)
-    }
-    fn new(val: Node) -> Self {
-        Tree(Some(Box::new(val)))
-    }
-    fn is_none(&self) -> bool {
-        self.0.is_none()
+
+/// 非負整数の多重集合
+/// ノードはVecに確保します。大きさが0になったノードは消さずに使い回します。
+/// 全体へのxorは遅延させて持つのでO(1)です。
+pub struct BinaryTrie {
+    nodes: Vec<Node>,
+    bitlen: i32,
+    /// 持っている値はnodesの値にこれをxorしたもの
+    xor: u64,
+}
+impl BinaryTrie {
+    pub fn new(bitlen: i32) -> Self {
+        BinaryTrie {
+            nodes: vec![Node::none()],
+            bitlen,
+            xor: 0,
+        }
     }
-    fn is_some(&self) -> bool {
-        self.0.is_some()
+    pub fn size(&self) -> usize {
+        self.nodes[0].size
     }
-    fn size(&self) -> usize {
-        if let Some(x) = self.0.as_ref() {
-            x.size
-        } else {
+    fn size_of(&self, v: usize) -> usize {
+        if v == NONE {
             0
+        } else {
+            self.nodes[v].size
         }
     }
-    fn insert(&mut self, val: u64, bit: i32) {
-        // 下からbit目のところです。
-        if self.is_none() {
-            *self = Tree::new(Node::none());
-        }
-        let node = self.0.as_mut().unwrap();
-        node.size += 1;
-        if bit >= 0 {
-            let f = (val >> bit) & 1;
-            node.child[f as usize].insert(val, bit - 1);
+    pub fn insert(&mut self, val: u64) {
+        let val = val ^ self.xor;
+        let mut now = 0;
+        self.nodes[now].size += 1;
+        for bit in (0..self.bitlen).rev() {
+            let f = ((val >> bit) & 1) as usize;
+            if self.nodes[now].child[f] == NONE {
+                self.nodes[now].child[f] = self.nodes.len();
+                self.nodes.push(Node::none());
+            }
+            now = self.nodes[now].child[f];
+            self.nodes[now].size += 1;
         }
     }
-    fn remove(&mut self, val: u64, bit: i32) {
-        assert!(self.is_some());
-        let node = self.0.as_mut().unwrap();
-        node.size -= 1;
-        if node.size == 0 {
-            *self = Tree::none();
-            return;
-        }
-        if bit >= 0 {
-            let f = (val >> bit) & 1;
-            node.child[f as usize].remove(val, bit - 1);
+    pub fn remove(&mut self, val: u64) {
+        //! valを1個消します。入っている必要があります。
+        assert!(self.count(val) > 0);
+        let val = val ^ self.xor;
+        let mut now = 0;
+        self.nodes[now].size -= 1;
+        for bit in (0..self.bitlen).rev() {
+            now = self.nodes[now].child[((val >> bit) & 1) as usize];
+            self.nodes[now].size -= 1;
         }
     }
-    fn min(&self, bit: i32, bias: u64) -> u64 {
-        //! min in ^bias
-        assert!(self.is_some());
-        let node = self.0.as_ref().unwrap();
-        if bit < 0 {
-            0
-        } else {
-            let f = (bias >> bit) & 1;
-            if f == 0 {
-                if node.child[0].is_some() {
-                    node.child[0].min(bit - 1, bias)
-                } else {
-                    node.child[1].min(bit - 1, bias) | (1 << bit)
-                }
-            } else {
-                if node.child[1].is_some() {
-                    node.child[1].min(bit - 1, bias) | (1 << bit)
-                } else {
-                    node.child[0].min(bit - 1, bias)
-                }
-            }
-        }
+    pub fn xor_all(&mut self, x: u64) {
+        //! 全部の値にxをxorします。O(1)
+        self.xor ^= x;
     }
-    fn max(&self, bit: i32, bias: u64) -> u64 {
-        assert!(self.is_some());
-        let node = self.0.as_ref().unwrap();
-        if bit < 0 {
-            0
-        } else {
-            let f = (bias >> bit) & 1;
-            if f == 0 {
-                if node.child[1].is_some() {
-                    node.child[1].min(bit - 1, bias) | (1 << bit)
-                } else {
-                    node.child[0].min(bit - 1, bias)
-                }
-            } else {
-                if node.child[0].is_some() {
-                    node.child[0].min(bit - 1, bias)
-                } else {
-                    node.child[1].min(bit - 1, bias) | (1 << bit)
-                }
+    pub fn count(&self, val: u64) -> usize {
+        let val = val ^ self.xor;
+        let mut now = 0;
+        for bit in (0..self.bitlen).rev() {
+            now = self.nodes[now].child[((val >> bit) & 1) as usize];
+            if now == NONE {
+                return 0;
             }
         }
+        self.nodes[now].size
     }
-    fn kth(&self, k: usize, bit: i32) -> u64 {
-        //! k is 0-indexed.
-        assert!(self.is_some());
-        let node = self.0.as_ref().unwrap();
-        if bit < 0 {
-            0
-        } else {
-            let m = node.child[0].size();
-            if k < m {
-                node.child[0].kth(k, bit - 1)
+    pub fn count_xor_less(&self, x: u64, k: u64) -> usize {
+        //! v ^ x < k となるvの個数
+        if self.bitlen < 64 && k >> self.bitlen != 0 {
+            return self.size();
+        }
+        let b = x ^ self.xor;
+        let mut now = 0;
+        let mut ret = 0;
+        for bit in (0..self.bitlen).rev() {
+            let f = ((b >> bit) & 1) as usize;
+            let next = if (k >> bit) & 1 == 1 {
+                ret += self.size_of(self.nodes[now].child[f]);
+                self.nodes[now].child[f ^ 1]
             } else {
-                node.child[1].kth(k - m, bit - 1) | (1 << bit)
+                self.nodes[now].child[f]
+            };
+            if next == NONE {
+                return ret;
             }
+            now = next;
         }
-    }
-    fn count_lower(&self, val: u64, bit: i32) -> usize {
-        //! count elements s.t. < val
-        if self.is_some() || bit < 0 {
-            0
-        } else {
-            let node = self.0.as_ref().unwrap();
-            let f = (val >> bit) & 1;
-            node.child[f as usize].count_lower(val, bit - 1)
-                + if f == 1 { node.child[0].size() } else { 0 }
-        }
-    }
-    fn count(&self, val: u64, bit: i32) -> usize {
-        if bit < 0 {
-            self.size()
-        } else if self.is_none() {
-            0
-        } else {
-            let node = self.0.as_ref().unwrap();
-            let f = (val >> bit) & 1;
-            node.child[f as usize].count(val, bit - 1)
-        }
-    }
-}
-
-pub struct BinaryTrie {
-    root: Tree,
-    bitlen: i32,
-}
-impl BinaryTrie {
-    pub fn new(bitlen: i32) -> Self {
-        BinaryTrie {
-            root: Tree::none(),
-            bitlen,
+        ret
+    }
+    pub fn kth_xor(&self, x: u64, mut k: usize) -> u64 {
+        //! v ^ x の小さい方からk番目 (0-indexed) になるvを返します。
+        assert!(k < self.size());
+        let b = x ^ self.xor;
+        let mut now = 0;
+        let mut ret = 0;
+        for bit in (0..self.bitlen).rev() {
+            let f = ((b >> bit) & 1) as usize;
+            let m = self.size_of(self.nodes[now].child[f]);
+            let f = if k < m {
+                f
+            } else {
+                k -= m;
+                f ^ 1
+            };
+            ret |= (f as u64) << bit;
+            now = self.nodes[now].child[f];
         }
-    }
-    pub fn size(&self) -> usize {
-        self.root.size()
-    }
-    pub fn insert(&mut self, val: u64) {
-        self.root.insert(val, self.bitlen - 1);
-    }
-    pub fn remove(&mut self, val: u64) {
-        self.root.remove(val, self.bitlen - 1);
+        ret ^ self.xor
     }
     pub fn max(&self) -> u64 {
-        self.root.max(self.bitlen - 1, 0)
+        self.kth(self.size() - 1)
     }
     pub fn min(&self) -> u64 {
-        self.root.min(self.bitlen - 1, 0)
+        self.kth(0)
     }
     pub fn max_xor(&self, x: u64) -> u64 {
-        self.root.max(self.bitlen - 1, x)
+        //! v ^ x が最大になるv
+        self.kth_xor(x, self.size() - 1)
     }
     pub fn min_xor(&self, x: u64) -> u64 {
-        self.root.min(self.bitlen - 1, x)
+        //! v ^ x が最小になるv
+        self.kth_xor(x, 0)
     }
     pub fn lower_bound(&self, val: u64) -> usize {
-        self.root.count_lower(val, self.bitlen - 1)
+        self.count_xor_less(0, val)
     }
     pub fn upper_bound(&self, val: u64) -> usize {
-        self.root.count_lower(val + 1, self.bitlen - 1)
+        // bitlen = 64だとval + 1があふれることがあります。
+        match val.checked_add(1) {
+            Some(k) => self.count_xor_less(0, k),
+            None => self.size(),
+        }
     }
     pub fn kth(&self, k: usize) -> u64 {
-        self.root.kth(k, self.bitlen - 1)
+        //! k is 0-indexed.
+        self.kth_xor(0, k)
     }
-    pub fn count(&self, val: u64) -> usize {
-        self.root.count(val, self.bitlen - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn binary_trie_test() {
+        let mut rng = Xorshift256::new();
+        let mut trie = BinaryTrie::new(6);
+        let mut naive: Vec<u64> = vec![];
+        for _ in 0..2000 {
+            let x = rng.gen() % 64;
+            match rng.gen() % 4 {
+                0 => {
+                    trie.insert(x);
+                    naive.push(x);
+                }
+                1 => {
+                    if let Some(i) = naive.iter().position(|&v| v == x) {
+                        trie.remove(x);
+                        naive.swap_remove(i);
+                    }
+                }
+                2 => {
+                    trie.xor_all(x);
+                    for v in &mut naive {
+                        *v ^= x;
+                    }
+                }
+                _ => {
+                    let k = rng.gen() % 65;
+                    let less = naive.iter().filter(|&&v| v ^ x < k).count();
+                    assert_eq!(trie.count_xor_less(x, k), less);
+                    assert_eq!(
+                        trie.lower_bound(x),
+                        naive.iter().filter(|&&v| v < x).count()
+                    );
+                    assert_eq!(
+                        trie.upper_bound(x),
+                        naive.iter().filter(|&&v| v <= x).count()
+                    );
+                    assert_eq!(trie.count(x), naive.iter().filter(|&&v| v == x).count());
+                    let mut sorted: Vec<u64> = naive.iter().map(|&v| v ^ x).collect();
+                    sorted.sort();
+                    for (i, &s) in sorted.iter().enumerate() {
+                        assert_eq!(trie.kth_xor(x, i) ^ x, s);
+                    }
+                    if !naive.is_empty() {
+                        assert_eq!(trie.min_xor(x) ^ x, sorted[0]);
+                        assert_eq!(trie.max_xor(x) ^ x, *sorted.last().unwrap());
+                        assert_eq!(trie.min(), *naive.iter().min().unwrap());
+                        assert_eq!(trie.max(), *naive.iter().max().unwrap());
+                    }
+                }
+            }
+            assert_eq!(trie.size(), naive.len());
+        }
+    }
+    #[test]
+    fn binary_trie_64_test() {
+        let mut rng = Xorshift256::new();
+        let mut trie = BinaryTrie::new(64);
+        let mut naive: Vec<u64> = vec![!0, !0 - 1, 0, 1 << 63];
+        naive.extend((0..50).map(|_| rng.gen()));
+        for &v in &naive {
+            trie.insert(v);
+        }
+        assert_eq!(trie.upper_bound(!0), naive.len());
+        assert_eq!(trie.lower_bound(!0), naive.len() - 1);
+        assert_eq!(trie.max(), !0);
+        for _ in 0..100 {
+            let (x, k) = (rng.gen(), rng.gen());
+            let less = naive.iter().filter(|&&v| v ^ x < k).count();
+            assert_eq!(trie.count_xor_less(x, k), less);
+            assert_eq!(
+                trie.upper_bound(x),
+                naive.iter().filter(|&&v| v <= x).count()
+            );
+            let mut sorted: Vec<u64> = naive.iter().map(|&v| v ^ x).collect();
+            sorted.sort();
+            for (i, &s) in sorted.iter().enumerate() {
+                assert_eq!(trie.kth_xor(x, i) ^ x, s);
+            }
+        }
     }
 }
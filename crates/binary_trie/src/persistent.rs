@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+#[derive(Clone, Copy)]
+struct Node {
+    size: usize,
+    child: [usize; 2],
+}
+
+/// 永続BinaryTrie
+/// push(v)するたびに版が増えます。版lから版rまでの差を見れば、
+/// 挿入した順での区間[l, r)についてxorの質問に答えられます。
+pub struct PersistentBinaryTrie {
+    // 0番は空のノードで、子は自分自身です。
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    bitlen: i32,
+}
+impl PersistentBinaryTrie {
+    pub fn new(bitlen: i32) -> Self {
+        PersistentBinaryTrie {
+            nodes: vec![Node {
+                size: 0,
+                child: [0; 2],
+            }],
+            roots: vec![0],
+            bitlen,
+        }
+    }
+    pub fn len(&self) -> usize {
+        //! 挿入した個数
+        self.roots.len() - 1
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn push(&mut self, val: u64) {
+        //! O(bitlen)
+        let mut prev = *self.roots.last().unwrap();
+        let root = self.nodes.len();
+        self.roots.push(root);
+        for bit in (0..self.bitlen).rev() {
+            let f = ((val >> bit) & 1) as usize;
+            let mut node = self.nodes[prev];
+            node.size += 1;
+            node.child[f] = self.nodes.len() + 1;
+            self.nodes.push(node);
+            prev = self.nodes[prev].child[f];
+        }
+        let mut leaf = self.nodes[prev];
+        leaf.size += 1;
+        self.nodes.push(leaf);
+    }
+    fn roots(&self, range: &Range<usize>) -> (usize, usize) {
+        assert!(range.start <= range.end && range.end <= self.len());
+        (self.roots[range.start], self.roots[range.end])
+    }
+    pub fn count(&self, range: Range<usize>, val: u64) -> usize {
+        //! range番目に挿入したもののうちvalの個数
+        self.count_xor_less(range, val, 1)
+    }
+    pub fn count_xor_less(&self, range: Range<usize>, x: u64, k: u64) -> usize {
+        //! range番目に挿入したもののうち v ^ x < k となるvの個数
+        let (mut l, mut r) = self.roots(&range);
+        if self.bitlen < 64 && k >> self.bitlen != 0 {
+            return self.nodes[r].size - self.nodes[l].size;
+        }
+        let mut ret = 0;
+        for bit in (0..self.bitlen).rev() {
+            let f = ((x >> bit) & 1) as usize;
+            let f = if (k >> bit) & 1 == 1 {
+                let (cl, cr) = (self.nodes[l].child[f], self.nodes[r].child[f]);
+                ret += self.nodes[cr].size - self.nodes[cl].size;
+                f ^ 1
+            } else {
+                f
+            };
+            l = self.nodes[l].child[f];
+            r = self.nodes[r].child[f];
+        }
+        ret
+    }
+    pub fn kth_xor(&self, range: Range<usize>, x: u64, mut k: usize) -> u64 {
+        //! range番目に挿入したもののうち、v ^ x の小さい方からk番目 (0-indexed) になるv
+        let (mut l, mut r) = self.roots(&range);
+        assert!(k < range.end - range.start);
+        let mut ret = 0;
+        for bit in (0..self.bitlen).rev() {
+            let f = ((x >> bit) & 1) as usize;
+            let (cl, cr) = (self.nodes[l].child[f], self.nodes[r].child[f]);
+            let m = self.nodes[cr].size - self.nodes[cl].size;
+            let f = if k < m {
+                f
+            } else {
+                k -= m;
+                f ^ 1
+            };
+            ret |= (f as u64) << bit;
+            l = self.nodes[l].child[f];
+            r = self.nodes[r].child[f];
+        }
+        ret
+    }
+    pub fn max_xor(&self, range: Range<usize>, x: u64) -> u64 {
+        //! v ^ x が最大になるv
+        assert!(range.start < range.end && range.end <= self.len());
+        let k = range.end - range.start - 1;
+        self.kth_xor(range, x, k)
+    }
+    pub fn min_xor(&self, range: Range<usize>, x: u64) -> u64 {
+        //! v ^ x が最小になるv
+        self.kth_xor(range, x, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn persistent_binary_trie_test() {
+        let mut rng = Xorshift256::new();
+        let mut trie = PersistentBinaryTrie::new(6);
+        let a: Vec<u64> = (0..100).map(|_| rng.gen() % 64).collect();
+        for &v in &a {
+            trie.push(v);
+        }
+        assert_eq!(trie.len(), a.len());
+        for _ in 0..300 {
+            let l = rng.gen() as usize % (a.len() + 1);
+            let r = l + rng.gen() as usize % (a.len() - l + 1);
+            let (x, k) = (rng.gen() % 64, rng.gen() % 65);
+            let part = &a[l..r];
+            let less = part.iter().filter(|&&v| v ^ x < k).count();
+            assert_eq!(trie.count_xor_less(l..r, x, k), less);
+            assert_eq!(
+                trie.count(l..r, x),
+                part.iter().filter(|&&v| v == x).count()
+            );
+            let mut sorted: Vec<u64> = part.iter().map(|&v| v ^ x).collect();
+            sorted.sort();
+            for (i, &s) in sorted.iter().enumerate() {
+                assert_eq!(trie.kth_xor(l..r, x, i) ^ x, s);
+            }
+            if l < r {
+                assert_eq!(trie.max_xor(l..r, x) ^ x, *sorted.last().unwrap());
+                assert_eq!(trie.min_xor(l..r, x) ^ x, sorted[0]);
+            }
+        }
+    }
+    #[test]
+    fn persistent_binary_trie_64_test() {
+        let mut rng = Xorshift256::new();
+        let mut trie = PersistentBinaryTrie::new(64);
+        let mut a: Vec<u64> = vec![!0, !0 - 1, 0, 1 << 63];
+        a.extend((0..50).map(|_| rng.gen()));
+        for &v in &a {
+            trie.push(v);
+        }
+        assert_eq!(trie.count(0..a.len(), !0), 1);
+        assert_eq!(trie.max_xor(0..a.len(), 0), !0);
+        for _ in 0..100 {
+            let l = rng.gen() as usize % (a.len() + 1);
+            let r = l + rng.gen() as usize % (a.len() - l + 1);
+            let (x, k) = (rng.gen(), rng.gen());
+            let part = &a[l..r];
+            let less = part.iter().filter(|&&v| v ^ x < k).count();
+            assert_eq!(trie.count_xor_less(l..r, x, k), less);
+            let mut sorted: Vec<u64> = part.iter().map(|&v| v ^ x).collect();
+            sorted.sort();
+            for (i, &s) in sorted.iter().enumerate() {
+                assert_eq!(trie.kth_xor(l..r, x, i) ^ x, s);
+            }
+        }
+    }
+}
@@ -1,27 +1,229 @@
+use extended_gcd::extgcd;
 use static_modint::*;
+use std::cell::RefCell;
 pub struct CombinationCalculator<M: Mod> {
-    fac: Box<[Modint<M>]>,
-    facinv: Box<[Modint<M>]>,
+    fac: RefCell<Vec<Modint<M>>>,
+    facinv: RefCell<Vec<Modint<M>>>,
 }
 impl<M: Mod> CombinationCalculator<M> {
     pub fn new(max: usize) -> Self {
-        let max = max + 1;
-        let mut fac = vec![Modint::new(1); max].into_boxed_slice();
-        let mut facinv = vec![Modint::new(1); max].into_boxed_slice();
-        for i in 2..max {
-            fac[i] = fac[i - 1] * i;
+        let ret = Self {
+            fac: RefCell::new(vec![Modint::new(1)]),
+            facinv: RefCell::new(vec![Modint::new(1)]),
+        };
+        ret.stretch(max);
+        ret
+    }
+    fn stretch(&self, n: usize) {
+        //! テーブルを[0, n]まで伸ばします。足りないときは倍々で伸ばします。
+        let len = self.fac.borrow().len();
+        if n < len {
+            return;
+        }
+        assert!((n as u64) < M::M, "n! is 0 mod p");
+        let max = ((n + 1).next_power_of_two()).min(M::M as usize);
+        let mut fac = self.fac.borrow_mut();
+        let mut facinv = self.facinv.borrow_mut();
+        for i in len..max {
+            let x = fac[i - 1] * i;
+            fac.push(x);
         }
+        facinv.resize(max, Modint::new(1));
         facinv[max - 1] = fac[max - 1].inv();
-        for i in (0..max - 1).rev() {
+        for i in (len..max - 1).rev() {
             facinv[i] = facinv[i + 1] * (i + 1);
         }
-        Self { fac, facinv }
+    }
+    pub fn fac(&self, n: usize) -> Modint<M> {
+        self.stretch(n);
+        self.fac.borrow()[n]
+    }
+    pub fn facinv(&self, n: usize) -> Modint<M> {
+        self.stretch(n);
+        self.facinv.borrow()[n]
+    }
+    pub fn inv(&self, n: usize) -> Modint<M> {
+        //! 1/n
+        assert!(n > 0);
+        self.facinv(n) * self.fac(n - 1)
     }
     pub fn calc(&self, n: usize, k: usize) -> Modint<M> {
+        //! nCk
+        if n < k {
+            Modint::new(0)
+        } else {
+            self.fac(n) * self.facinv(k) * self.facinv(n - k)
+        }
+    }
+    pub fn perm(&self, n: usize, k: usize) -> Modint<M> {
+        //! nPk
         if n < k {
             Modint::new(0)
         } else {
-            self.fac[n] * self.facinv[k] * self.facinv[n - k]
+            self.fac(n) * self.facinv(n - k)
+        }
+    }
+    pub fn homo(&self, n: usize, k: usize) -> Modint<M> {
+        //! nHk = (n+k-1)Ck
+        if n == 0 {
+            Modint::new((k == 0) as u64)
+        } else {
+            self.calc(n + k - 1, k)
+        }
+    }
+    pub fn catalan(&self, n: usize) -> Modint<M> {
+        self.calc(2 * n, n) * self.inv(n + 1)
+    }
+    pub fn calc_small_k(&self, n: u64, k: usize) -> Modint<M> {
+        //! nCk in O(k). n can be huge (k < p is required).
+        if n < k as u64 {
+            return Modint::new(0);
+        }
+        let mut ret = self.facinv(k);
+        for i in 0..k as u64 {
+            ret *= n - i;
+        }
+        ret
+    }
+    pub fn lucas(&self, mut n: u64, mut k: u64) -> Modint<M> {
+        //! nCk by Lucas's theorem. O(p) for the table, O(log_p n) per query.
+        let mut ret = Modint::new(1);
+        while k > 0 {
+            let (ni, ki) = ((n % M::M) as usize, (k % M::M) as usize);
+            if ni < ki {
+                return Modint::new(0);
+            }
+            ret *= self.calc(ni, ki);
+            n /= M::M;
+            k /= M::M;
+        }
+        ret
+    }
+}
+impl<M: Mod> Default for CombinationCalculator<M> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// nCk mod p^e (Granville)
+/// M::M must be p^e.
+pub struct PrimePowerCombination<M: Mod> {
+    p: u64,
+    e: u32,
+    fac: Box<[Modint<M>]>,
+}
+impl<M: Mod> PrimePowerCombination<M> {
+    pub fn new(p: u64, e: u32) -> Self {
+        //! O(p^e)
+        assert!(e > 0 && p.pow(e) == M::M);
+        let pe = M::M as usize;
+        // fac[i] = pと互いに素なi以下の数の積
+        let mut fac = vec![Modint::new(1); pe].into_boxed_slice();
+        let mut r = 0;
+        for i in 1..pe {
+            r += 1;
+            if r == p {
+                r = 0;
+                fac[i] = fac[i - 1];
+            } else {
+                fac[i] = fac[i - 1] * i;
+            }
+        }
+        Self { p, e, fac }
+    }
+    fn pow(mut x: Modint<M>, mut e: u64) -> Modint<M> {
+        // Modint::powは素数modを仮定しているので使えません。
+        let mut ret = Modint::new(1);
+        while e > 0 {
+            if e & 1 == 1 {
+                ret *= x;
+            }
+            x *= x;
+            e >>= 1;
+        }
+        ret
+    }
+    fn inv(x: Modint<M>) -> Modint<M> {
+        let (g, a, _) = extgcd(x.value() as i64, M::M as i64);
+        assert_eq!(g, 1);
+        Modint::from(a)
+    }
+    fn fac_without_p(&self, mut n: u64) -> (Modint<M>, u64) {
+        //! return: (n!からpを除いたもの, n!に含まれるpの個数)
+        let mut ret = Modint::new(1);
+        let mut cnt = 0;
+        while n > 0 {
+            ret *= Self::pow(self.fac[M::M as usize - 1], n / M::M);
+            ret *= self.fac[(n % M::M) as usize];
+            n /= self.p;
+            cnt += n;
+        }
+        (ret, cnt)
+    }
+    pub fn calc(&self, n: u64, k: u64) -> Modint<M> {
+        if n < k {
+            return Modint::new(0);
+        }
+        let (a, ca) = self.fac_without_p(n);
+        let (b, cb) = self.fac_without_p(k);
+        let (c, cc) = self.fac_without_p(n - k);
+        let cnt = ca - cb - cc;
+        if cnt >= self.e as u64 {
+            return Modint::new(0);
+        }
+        a * Self::inv(b * c) * Self::pow(Modint::new(self.p), cnt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    define_mod!(P7, 7);
+    define_mod!(P3E4, 81);
+    define_mod!(P2E5, 32);
+    fn pascal(n: usize, m: u64) -> Vec<Vec<u64>> {
+        let mut c = vec![vec![0; n + 1]; n + 1];
+        for i in 0..=n {
+            c[i][0] = 1 % m;
+            for j in 1..=i {
+                c[i][j] = (c[i - 1][j - 1] + c[i - 1][j]) % m;
+            }
+        }
+        c
+    }
+    #[test]
+    fn combination_test() {
+        let comb = CombinationCalculator::<P1000000007>::default();
+        let c = pascal(60, 1_000_000_007);
+        for (n, row) in c.iter().enumerate() {
+            for (k, &x) in row.iter().enumerate().take(n + 1) {
+                assert_eq!(comb.calc(n, k).value(), x);
+                assert_eq!(comb.calc_small_k(n as u64, k).value(), x);
+                assert_eq!(comb.perm(n, k), comb.calc(n, k) * comb.fac(k));
+            }
+        }
+        assert_eq!(comb.homo(3, 2).value(), 6);
+        let catalan = [1, 1, 2, 5, 14, 42, 132, 429];
+        for (i, &x) in catalan.iter().enumerate() {
+            assert_eq!(comb.catalan(i).value(), x);
+        }
+        assert_eq!(comb.inv(2) * 2, Modint::new(1));
+    }
+    #[test]
+    fn lucas_and_granville_test() {
+        let c7 = pascal(200, 7);
+        let c81 = pascal(200, 81);
+        let c32 = pascal(200, 32);
+        let lucas = CombinationCalculator::<P7>::new(6);
+        let g81 = PrimePowerCombination::<P3E4>::new(3, 4);
+        let g32 = PrimePowerCombination::<P2E5>::new(2, 5);
+        for n in 0..=200 {
+            for k in 0..=n {
+                assert_eq!(lucas.lucas(n as u64, k as u64).value(), c7[n][k]);
+                assert_eq!(g81.calc(n as u64, k as u64).value(), c81[n][k]);
+                assert_eq!(g32.calc(n as u64, k as u64).value(), c32[n][k]);
+            }
         }
     }
 }
@@ -0,0 +1,4 @@
+mod max_flow;
+mod min_cost_flow;
+pub use max_flow::*;
+pub use min_cost_flow::*;
@@ -0,0 +1,209 @@
+use algebra::Zero;
+use std::collections::VecDeque;
+use std::ops::Sub;
+
+const NONE: usize = !0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge<C> {
+    pub from: usize,
+    pub to: usize,
+    pub cap: C,
+    pub flow: C,
+}
+
+#[derive(Clone, Copy)]
+struct InnerEdge<C> {
+    to: usize,
+    rev: usize,
+    cap: C,
+}
+
+/// Dinic
+pub struct MaxFlow<C> {
+    g: Vec<Vec<InnerEdge<C>>>,
+    pos: Vec<(usize, usize)>,
+    level: Vec<usize>,
+    iter: Vec<usize>,
+}
+impl<C: Zero + Ord + Copy + Sub<Output = C>> MaxFlow<C> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            g: vec![Vec::new(); n],
+            pos: Vec::new(),
+            level: vec![NONE; n],
+            iter: vec![0; n],
+        }
+    }
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: C) -> usize {
+        //! return: 辺の番号。get_edgeで流量を見るのに使います。
+        assert!(from < self.g.len() && to < self.g.len());
+        assert!(cap >= C::zero());
+        let id = self.pos.len();
+        let from_id = self.g[from].len();
+        let mut to_id = self.g[to].len();
+        if from == to {
+            to_id += 1;
+        }
+        self.pos.push((from, from_id));
+        self.g[from].push(InnerEdge {
+            to,
+            rev: to_id,
+            cap,
+        });
+        self.g[to].push(InnerEdge {
+            to: from,
+            rev: from_id,
+            cap: C::zero(),
+        });
+        id
+    }
+    pub fn get_edge(&self, i: usize) -> Edge<C> {
+        let (from, idx) = self.pos[i];
+        let e = self.g[from][idx];
+        let re = self.g[e.to][e.rev];
+        Edge {
+            from,
+            to: e.to,
+            cap: e.cap + re.cap,
+            flow: re.cap,
+        }
+    }
+    pub fn edges(&self) -> Vec<Edge<C>> {
+        (0..self.pos.len()).map(|i| self.get_edge(i)).collect()
+    }
+    fn bfs(&mut self, s: usize) {
+        for x in self.level.iter_mut() {
+            *x = NONE;
+        }
+        self.level[s] = 0;
+        let mut queue = VecDeque::new();
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            for e in &self.g[v] {
+                if e.cap > C::zero() && self.level[e.to] == NONE {
+                    self.level[e.to] = self.level[v] + 1;
+                    queue.push_back(e.to);
+                }
+            }
+        }
+    }
+    fn dfs(&mut self, v: usize, t: usize, f: C) -> C {
+        if v == t {
+            return f;
+        }
+        let mut res = C::zero();
+        while self.iter[v] < self.g[v].len() {
+            let i = self.iter[v];
+            let e = self.g[v][i];
+            if e.cap > C::zero() && self.level[e.to] == self.level[v] + 1 {
+                let d = self.dfs(e.to, t, (f - res).min(e.cap));
+                if d > C::zero() {
+                    self.g[v][i].cap = e.cap - d;
+                    self.g[e.to][e.rev].cap += d;
+                    res += d;
+                    if res == f {
+                        return res;
+                    }
+                }
+            }
+            self.iter[v] += 1;
+        }
+        res
+    }
+    pub fn flow_with_limit(&mut self, s: usize, t: usize, limit: C) -> C {
+        //! O(n^2 m)
+        assert!(s != t);
+        let mut flow = C::zero();
+        while flow < limit {
+            self.bfs(s);
+            if self.level[t] == NONE {
+                break;
+            }
+            for x in self.iter.iter_mut() {
+                *x = 0;
+            }
+            let f = self.dfs(s, t, limit - flow);
+            if f == C::zero() {
+                break;
+            }
+            flow += f;
+        }
+        flow
+    }
+    pub fn flow(&mut self, s: usize, t: usize) -> C {
+        let mut limit = C::zero();
+        for e in &self.g[s] {
+            limit += e.cap;
+        }
+        self.flow_with_limit(s, t, limit)
+    }
+    pub fn min_cut(&self, s: usize) -> Vec<bool> {
+        //! 流した後に残余グラフでsから行ける頂点
+        let mut visited = vec![false; self.g.len()];
+        visited[s] = true;
+        let mut stack = vec![s];
+        while let Some(v) = stack.pop() {
+            for e in &self.g[v] {
+                if e.cap > C::zero() && !visited[e.to] {
+                    visited[e.to] = true;
+                    stack.push(e.to);
+                }
+            }
+        }
+        visited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn max_flow_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..100 {
+            let n = 2 + rng.gen() as usize % 6;
+            let m = rng.gen() as usize % 15;
+            let edges: Vec<_> = (0..m)
+                .map(|_| {
+                    let u = rng.gen() as usize % n;
+                    let v = rng.gen() as usize % n;
+                    (u, v, (rng.gen() % 10) as i64)
+                })
+                .collect();
+            let mut g = MaxFlow::new(n);
+            for &(u, v, c) in &edges {
+                g.add_edge(u, v, c);
+            }
+            let f = g.flow(0, n - 1);
+            let mut balance = vec![0; n];
+            for (i, e) in g.edges().iter().enumerate() {
+                assert_eq!((e.from, e.to, e.cap), edges[i]);
+                assert!(0 <= e.flow && e.flow <= e.cap);
+                balance[e.from] -= e.flow;
+                balance[e.to] += e.flow;
+            }
+            assert_eq!(balance[0], -f);
+            assert_eq!(balance[n - 1], f);
+            assert!(balance[1..n - 1].iter().all(|&x| x == 0));
+            // 最小カットとの比較 (全部の分け方を試します)
+            let cut = g.min_cut(0);
+            assert!(cut[0] && !cut[n - 1]);
+            let cut_cap = |side: &dyn Fn(usize) -> bool| -> i64 {
+                edges
+                    .iter()
+                    .filter(|e| side(e.0) && !side(e.1))
+                    .map(|e| e.2)
+                    .sum()
+            };
+            assert_eq!(cut_cap(&|v| cut[v]), f);
+            let min = (0..1 << n)
+                .filter(|&b: &usize| b & 1 == 1 && b >> (n - 1) & 1 == 0)
+                .map(|b| cut_cap(&|v| b >> v & 1 == 1))
+                .min()
+                .unwrap();
+            assert_eq!(min, f);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostEdge {
+    pub from: usize,
+    pub to: usize,
+    pub cap: i64,
+    pub flow: i64,
+    pub cost: i64,
+}
+
+#[derive(Clone, Copy)]
+struct InnerEdge {
+    to: usize,
+    rev: usize,
+    cap: i64,
+    cost: i64,
+}
+
+/// Primal-Dual (ポテンシャルつきDijkstraで最短路を流します)
+pub struct MinCostFlow {
+    g: Vec<Vec<InnerEdge>>,
+    pos: Vec<(usize, usize)>,
+}
+impl MinCostFlow {
+    pub fn new(n: usize) -> Self {
+        Self {
+            g: vec![Vec::new(); n],
+            pos: Vec::new(),
+        }
+    }
+    pub fn add_edge(&mut self, from: usize, to: usize, cap: i64, cost: i64) -> usize {
+        //! costは非負にしてください。
+        assert!(from < self.g.len() && to < self.g.len());
+        assert!(cap >= 0 && cost >= 0);
+        let id = self.pos.len();
+        let from_id = self.g[from].len();
+        let mut to_id = self.g[to].len();
+        if from == to {
+            to_id += 1;
+        }
+        self.pos.push((from, from_id));
+        self.g[from].push(InnerEdge {
+            to,
+            rev: to_id,
+            cap,
+            cost,
+        });
+        self.g[to].push(InnerEdge {
+            to: from,
+            rev: from_id,
+            cap: 0,
+            cost: -cost,
+        });
+        id
+    }
+    pub fn get_edge(&self, i: usize) -> CostEdge {
+        let (from, idx) = self.pos[i];
+        let e = self.g[from][idx];
+        let re = self.g[e.to][e.rev];
+        CostEdge {
+            from,
+            to: e.to,
+            cap: e.cap + re.cap,
+            flow: re.cap,
+            cost: e.cost,
+        }
+    }
+    pub fn edges(&self) -> Vec<CostEdge> {
+        (0..self.pos.len()).map(|i| self.get_edge(i)).collect()
+    }
+    fn dual_ref(&self, s: usize, t: usize, dual: &mut [i64], prev: &mut [(usize, usize)]) -> bool {
+        let n = self.g.len();
+        let mut dist = vec![None; n];
+        let mut visited = vec![false; n];
+        let mut heap = BinaryHeap::new();
+        dist[s] = Some(0);
+        heap.push(Reverse((0, s)));
+        while let Some(Reverse((d, v))) = heap.pop() {
+            if visited[v] {
+                continue;
+            }
+            visited[v] = true;
+            if v == t {
+                break;
+            }
+            for (i, e) in self.g[v].iter().enumerate() {
+                if e.cap == 0 {
+                    continue;
+                }
+                // 被約コストは非負です。
+                let nd = d + e.cost - dual[e.to] + dual[v];
+                match dist[e.to] {
+                    Some(x) if x <= nd => {}
+                    _ => {
+                        dist[e.to] = Some(nd);
+                        prev[e.to] = (v, i);
+                        heap.push(Reverse((nd, e.to)));
+                    }
+                }
+            }
+        }
+        if !visited[t] {
+            return false;
+        }
+        let dt = dist[t].unwrap();
+        for v in 0..n {
+            if visited[v] {
+                dual[v] -= dt - dist[v].unwrap();
+            }
+        }
+        true
+    }
+    pub fn slope_with_limit(&mut self, s: usize, t: usize, limit: i64) -> Vec<(i64, i64)> {
+        //! O(F (n + m) log m)
+        //! return: (流量, コスト)の折れ線の頂点。(0, 0)から始まります。
+        //! 流した後の残余グラフには負辺があるので、呼ぶのは一回だけにしてください。
+        assert!(s != t);
+        let n = self.g.len();
+        let mut dual = vec![0; n];
+        let mut prev = vec![(0, 0); n];
+        let (mut flow, mut cost) = (0, 0);
+        let mut prev_cost_per_flow = None;
+        let mut ret = vec![(0, 0)];
+        while flow < limit {
+            if !self.dual_ref(s, t, &mut dual, &mut prev) {
+                break;
+            }
+            let mut c = limit - flow;
+            let mut v = t;
+            while v != s {
+                let (p, i) = prev[v];
+                c = c.min(self.g[p][i].cap);
+                v = p;
+            }
+            let mut v = t;
+            while v != s {
+                let (p, i) = prev[v];
+                let rev = self.g[p][i].rev;
+                self.g[p][i].cap -= c;
+                self.g[v][rev].cap += c;
+                v = p;
+            }
+            let d = dual[t] - dual[s];
+            flow += c;
+            cost += c * d;
+            if prev_cost_per_flow == Some(d) {
+                ret.pop();
+            }
+            ret.push((flow, cost));
+            prev_cost_per_flow = Some(d);
+        }
+        ret
+    }
+    pub fn slope(&mut self, s: usize, t: usize) -> Vec<(i64, i64)> {
+        let limit = self.g[s].iter().map(|e| e.cap).sum();
+        self.slope_with_limit(s, t, limit)
+    }
+    pub fn flow_with_limit(&mut self, s: usize, t: usize, limit: i64) -> (i64, i64) {
+        //! return: (流量, コスト)
+        *self.slope_with_limit(s, t, limit).last().unwrap()
+    }
+    pub fn flow(&mut self, s: usize, t: usize) -> (i64, i64) {
+        *self.slope(s, t).last().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaxFlow;
+    use xorshift::Xorshift256;
+    fn brute(n: usize, edges: &[(usize, usize, i64, i64)], s: usize, t: usize) -> Vec<i64> {
+        //! Bellman-Fordで1ずつ流します。return: 流量kのときの最小コスト
+        let mut from: Vec<usize> = vec![];
+        let mut to: Vec<usize> = vec![];
+        let mut cap: Vec<i64> = vec![];
+        let mut cost: Vec<i64> = vec![];
+        for &(u, v, c, w) in edges {
+            from.extend(&[u, v]);
+            to.extend(&[v, u]);
+            cap.extend(&[c, 0]);
+            cost.extend(&[w, -w]);
+        }
+        let mut ret = vec![0];
+        loop {
+            let mut dist = vec![None; n];
+            let mut prev = vec![0; n];
+            dist[s] = Some(0);
+            for _ in 0..n {
+                for i in 0..to.len() {
+                    if cap[i] == 0 {
+                        continue;
+                    }
+                    if let Some(d) = dist[from[i]] {
+                        let nd = d + cost[i];
+                        match dist[to[i]] {
+                            Some(x) if x <= nd => {}
+                            _ => {
+                                dist[to[i]] = Some(nd);
+                                prev[to[i]] = i;
+                            }
+                        }
+                    }
+                }
+            }
+            let d = match dist[t] {
+                Some(d) => d,
+                None => return ret,
+            };
+            let mut v = t;
+            while v != s {
+                let i = prev[v];
+                cap[i] -= 1;
+                cap[i ^ 1] += 1;
+                v = from[i];
+            }
+            let last = *ret.last().unwrap();
+            ret.push(last + d);
+        }
+    }
+    #[test]
+    fn min_cost_flow_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..100 {
+            let n = 2 + rng.gen() as usize % 6;
+            let m = rng.gen() as usize % 15;
+            let edges: Vec<_> = (0..m)
+                .map(|_| {
+                    let u = rng.gen() as usize % n;
+                    let v = rng.gen() as usize % n;
+                    (u, v, (rng.gen() % 5) as i64, (rng.gen() % 10) as i64)
+                })
+                .collect();
+            let mut g = MinCostFlow::new(n);
+            let mut mf = MaxFlow::new(n);
+            for &(u, v, c, w) in &edges {
+                g.add_edge(u, v, c, w);
+                mf.add_edge(u, v, c);
+            }
+            let slope = g.slope(0, n - 1);
+            let naive = brute(n, &edges, 0, n - 1);
+            assert_eq!(slope.last().unwrap().0, mf.flow(0, n - 1));
+            assert_eq!(slope.last().unwrap().0 as usize, naive.len() - 1);
+            for w in slope.windows(2) {
+                let ((f0, c0), (f1, c1)) = (w[0], w[1]);
+                for k in f0..=f1 {
+                    assert_eq!(c0 + (c1 - c0) / (f1 - f0) * (k - f0), naive[k as usize]);
+                }
+            }
+            let cost: i64 = g.edges().iter().map(|e| e.flow * e.cost).sum();
+            assert_eq!(cost, slope.last().unwrap().1);
+        }
+    }
+}
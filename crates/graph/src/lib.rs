@@ -0,0 +1,168 @@
+use mario::token::Token;
+use mario::MarIo;
+use std::io::{BufRead, Write};
+use std::ops::Index;
+mod shortest_path;
+pub use shortest_path::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge<W> {
+    pub to: usize,
+    pub weight: W,
+    /// 入力での辺番号。無向辺は両向きで同じidです。
+    pub id: usize,
+}
+
+/// CSR形式の隣接リスト
+#[derive(Clone, Debug)]
+pub struct Graph<W> {
+    start: Box<[usize]>,
+    adj: Box<[Edge<W>]>,
+    edge_count: usize,
+}
+impl<W> Graph<W> {
+    pub fn size(&self) -> usize {
+        //! 頂点数
+        self.start.len() - 1
+    }
+    pub fn edge_count(&self) -> usize {
+        //! 入力で与えた辺の本数
+        self.edge_count
+    }
+    pub fn edges(&self, v: usize) -> &[Edge<W>] {
+        &self.adj[self.start[v]..self.start[v + 1]]
+    }
+}
+impl<W: Copy> Graph<W> {
+    fn build(n: usize, edges: &[(usize, usize, W)], directed: bool) -> Self {
+        let mut start = vec![0; n + 1];
+        for &(u, v, _) in edges {
+            assert!(u < n && v < n);
+            start[u + 1] += 1;
+            if !directed {
+                start[v + 1] += 1;
+            }
+        }
+        for i in 0..n {
+            start[i + 1] += start[i];
+        }
+        let mut list = Vec::with_capacity(start[n]);
+        for (id, &(u, v, weight)) in edges.iter().enumerate() {
+            list.push((u, Edge { to: v, weight, id }));
+            if !directed {
+                list.push((v, Edge { to: u, weight, id }));
+            }
+        }
+        let mut pos = start.clone();
+        let mut order = vec![0; list.len()];
+        for (i, &(u, _)) in list.iter().enumerate() {
+            order[pos[u]] = i;
+            pos[u] += 1;
+        }
+        let adj: Vec<_> = order.into_iter().map(|i| list[i].1).collect();
+        Self {
+            start: start.into_boxed_slice(),
+            adj: adj.into_boxed_slice(),
+            edge_count: edges.len(),
+        }
+    }
+    pub fn directed(n: usize, edges: &[(usize, usize, W)]) -> Self {
+        Self::build(n, edges, true)
+    }
+    pub fn undirected(n: usize, edges: &[(usize, usize, W)]) -> Self {
+        Self::build(n, edges, false)
+    }
+    pub fn reversed(&self) -> Self {
+        //! 有向グラフの辺を全部逆向きにしたもの (idは保たれます)
+        let mut edges = Vec::with_capacity(self.edge_count);
+        let mut ids = Vec::with_capacity(self.edge_count);
+        for v in 0..self.size() {
+            for e in self.edges(v) {
+                edges.push((e.to, v, e.weight));
+                ids.push(e.id);
+            }
+        }
+        let mut ret = Self::build(self.size(), &edges, true);
+        for e in ret.adj.iter_mut() {
+            e.id = ids[e.id];
+        }
+        ret.edge_count = self.edge_count;
+        ret
+    }
+}
+impl Graph<()> {
+    pub fn directed_unweighted(n: usize, edges: &[(usize, usize)]) -> Self {
+        let edges: Vec<_> = edges.iter().map(|&(u, v)| (u, v, ())).collect();
+        Self::build(n, &edges, true)
+    }
+    pub fn undirected_unweighted(n: usize, edges: &[(usize, usize)]) -> Self {
+        let edges: Vec<_> = edges.iter().map(|&(u, v)| (u, v, ())).collect();
+        Self::build(n, &edges, false)
+    }
+}
+impl<W> Index<usize> for Graph<W> {
+    type Output = [Edge<W>];
+    fn index(&self, v: usize) -> &[Edge<W>] {
+        &self.adj[self.start[v]..self.start[v + 1]]
+    }
+}
+
+/// MarIoから辺を読むやつ
+/// 頂点はusize1などのTokenで読みます。
+pub trait ReadGraph {
+    fn edges<V: Token<Output = usize>, W: Token>(
+        &mut self,
+        m: usize,
+    ) -> Vec<(usize, usize, W::Output)>;
+    fn unweighted_edges<V: Token<Output = usize>>(&mut self, m: usize) -> Vec<(usize, usize)>;
+}
+impl<I: BufRead, O: Write> ReadGraph for MarIo<I, O> {
+    fn edges<V: Token<Output = usize>, W: Token>(
+        &mut self,
+        m: usize,
+    ) -> Vec<(usize, usize, W::Output)> {
+        (0..m)
+            .map(|_| {
+                let u = self.parse::<V>();
+                let v = self.parse::<V>();
+                (u, v, self.parse::<W>())
+            })
+            .collect()
+    }
+    fn unweighted_edges<V: Token<Output = usize>>(&mut self, m: usize) -> Vec<(usize, usize)> {
+        (0..m)
+            .map(|_| {
+                let u = self.parse::<V>();
+                (u, self.parse::<V>())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mario::prelude::*;
+    #[test]
+    fn graph_test() {
+        let input = b"4 5\n1 2 3\n2 3 1\n1 3 5\n3 4 2\n4 1 7\n";
+        let mut io = MarIo::new(&input[..], Vec::new());
+        let (n, m) = read!(io, usize, usize);
+        let edges = io.edges::<usize1, i64>(m);
+        assert_eq!(edges[0], (0, 1, 3));
+        let g = Graph::directed(n, &edges);
+        assert_eq!(g.size(), 4);
+        assert_eq!(g.edge_count(), 5);
+        let mut to: Vec<_> = g[0].iter().map(|e| (e.to, e.weight, e.id)).collect();
+        to.sort();
+        assert_eq!(to, vec![(1, 3, 0), (2, 5, 2)]);
+        let r = g.reversed();
+        assert_eq!(
+            r[0].iter().map(|e| (e.to, e.id)).collect::<Vec<_>>(),
+            vec![(3, 4)]
+        );
+        let u = Graph::undirected_unweighted(3, &[(0, 1), (1, 2)]);
+        assert_eq!(u[1].len(), 2);
+        assert_eq!(u.edges(2)[0].id, 1);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::Graph;
+use algebra::Zero;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// 距離。Noneが到達不能です。
+pub type Dist<W> = Vec<Option<W>>;
+/// 最短路木での親。始点はNoneです。
+pub type Parent = Vec<Option<usize>>;
+
+fn relax<W: Ord + Copy>(d: &mut Option<W>, x: W) -> bool {
+    match *d {
+        Some(y) if y <= x => false,
+        _ => {
+            *d = Some(x);
+            true
+        }
+    }
+}
+
+pub fn restore_path(parent: &[Option<usize>], t: usize) -> Vec<usize> {
+    //! parentを辿ってtまでのパスを復元します。始点から順に並びます。
+    let mut path = vec![t];
+    let mut v = t;
+    while let Some(p) = parent[v] {
+        path.push(p);
+        v = p;
+    }
+    path.reverse();
+    path
+}
+
+pub fn dijkstra<W: Zero + Ord + Copy>(g: &Graph<W>, s: usize) -> (Dist<W>, Parent) {
+    //! O((n + m)log m)
+    let n = g.size();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut heap = BinaryHeap::new();
+    dist[s] = Some(W::zero());
+    heap.push(Reverse((W::zero(), s)));
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if dist[v] != Some(d) {
+            continue;
+        }
+        for e in &g[v] {
+            let nd = d + e.weight;
+            if relax(&mut dist[e.to], nd) {
+                parent[e.to] = Some(v);
+                heap.push(Reverse((nd, e.to)));
+            }
+        }
+    }
+    (dist, parent)
+}
+
+pub fn bfs01<W: Zero + Ord + Copy>(g: &Graph<W>, s: usize) -> (Dist<W>, Parent) {
+    //! 重みが0か1のときのやつ。O(n + m)
+    let n = g.size();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut deque = VecDeque::new();
+    dist[s] = Some(W::zero());
+    deque.push_back((W::zero(), s));
+    while let Some((d, v)) = deque.pop_front() {
+        if dist[v] != Some(d) {
+            continue;
+        }
+        for e in &g[v] {
+            let nd = d + e.weight;
+            if relax(&mut dist[e.to], nd) {
+                parent[e.to] = Some(v);
+                if e.weight == W::zero() {
+                    deque.push_front((nd, e.to));
+                } else {
+                    deque.push_back((nd, e.to));
+                }
+            }
+        }
+    }
+    (dist, parent)
+}
+
+pub fn bellman_ford<W: Zero + Ord + Copy>(g: &Graph<W>, s: usize) -> (Dist<W>, Parent, Vec<bool>) {
+    //! O(nm)
+    //! return: (dist, parent, negative)
+    //! negative[v]がtrueなら、sからvへの距離はいくらでも小さくできます。
+    let n = g.size();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut negative = vec![false; n];
+    dist[s] = Some(W::zero());
+    for i in 0..2 * n {
+        let mut updated = false;
+        for v in 0..n {
+            let d = match dist[v] {
+                Some(d) => d,
+                None => continue,
+            };
+            for e in &g[v] {
+                if i < n {
+                    if relax(&mut dist[e.to], d + e.weight) {
+                        parent[e.to] = Some(v);
+                        updated = true;
+                    }
+                } else if negative[v] || dist[e.to].map(|x| d + e.weight < x) == Some(true) {
+                    // n回目以降に更新されるのは負閉路の影響を受けるところです。
+                    if !negative[e.to] {
+                        negative[e.to] = true;
+                        updated = true;
+                    }
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+    (dist, parent, negative)
+}
+
+pub fn floyd_warshall<W: Zero + Ord + Copy>(g: &Graph<W>) -> (Vec<Dist<W>>, Vec<Parent>) {
+    //! O(n^3)
+    //! parent[s]はsを始点としたときのparent配列です。
+    //! dist[v][v] < 0 となるvがあれば負閉路があります。
+    let n = g.size();
+    let mut dist = vec![vec![None; n]; n];
+    let mut parent = vec![vec![None; n]; n];
+    for v in 0..n {
+        dist[v][v] = Some(W::zero());
+        for e in &g[v] {
+            if relax(&mut dist[v][e.to], e.weight) {
+                parent[v][e.to] = Some(v);
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            let dik = match dist[i][k] {
+                Some(d) => d,
+                None => continue,
+            };
+            for j in 0..n {
+                if let Some(dkj) = dist[k][j] {
+                    if relax(&mut dist[i][j], dik + dkj) {
+                        parent[i][j] = parent[k][j];
+                    }
+                }
+            }
+        }
+    }
+    (dist, parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn shortest_path_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..30 {
+            let n = 1 + rng.gen() as usize % 12;
+            let m = rng.gen() as usize % 40;
+            let edges: Vec<_> = (0..m)
+                .map(|_| {
+                    let u = rng.gen() as usize % n;
+                    let v = rng.gen() as usize % n;
+                    (u, v, (rng.gen() % 2) as i64)
+                })
+                .collect();
+            let g = Graph::directed(n, &edges);
+            let (dist, parent) = dijkstra(&g, 0);
+            let (dist01, _) = bfs01(&g, 0);
+            let (dist_bf, _, negative) = bellman_ford(&g, 0);
+            let (dist_fw, parent_fw) = floyd_warshall(&g);
+            assert_eq!(dist, dist01);
+            assert_eq!(dist, dist_bf);
+            assert_eq!(dist, dist_fw[0]);
+            assert!(negative.iter().all(|&x| !x));
+            for (t, &d) in dist.iter().enumerate() {
+                if let Some(d) = d {
+                    for parent in [&parent, &parent_fw[0]].iter() {
+                        let path = restore_path(parent, t);
+                        assert_eq!(path[0], 0);
+                        let len: i64 = path
+                            .windows(2)
+                            .map(|w| {
+                                edges
+                                    .iter()
+                                    .filter(|e| e.0 == w[0] && e.1 == w[1])
+                                    .map(|e| e.2)
+                                    .min()
+                                    .unwrap()
+                            })
+                            .sum();
+                        assert_eq!(len, d);
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn negative_cycle_test() {
+        // 0 -> 1 -> 2 -> 1 (負閉路) -> 3, 4は別
+        let g = Graph::directed(5, &[(0, 1, 1), (1, 2, -3), (2, 1, 1), (2, 3, 5), (0, 4, 2)]);
+        let (dist, _, negative) = bellman_ford(&g, 0);
+        assert_eq!(negative, vec![false, true, true, true, false]);
+        assert_eq!(dist[4], Some(2));
+        let (dist, _) = floyd_warshall(&g);
+        assert!(dist[1][1].unwrap() < 0);
+    }
+}
@@ -0,0 +1,209 @@
+use graph::Graph;
+use std::ops::Range;
+mod path_segment_tree;
+pub use path_segment_tree::*;
+
+const NONE: usize = !0;
+
+/// HL分解。各頂点にpos(v)の位置を割り当てて、パスをO(log n)個の区間に分けます。
+#[derive(Clone, Debug)]
+pub struct HeavyLightDecomposition {
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    head: Vec<usize>,
+    pos: Vec<usize>,
+    out: Vec<usize>,
+    order: Vec<usize>,
+}
+impl HeavyLightDecomposition {
+    pub fn new<W>(g: &Graph<W>, root: usize) -> Self {
+        //! 無向の木を渡してください。O(n)
+        let n = g.size();
+        let mut parent = vec![NONE; n];
+        let mut depth = vec![0; n];
+        let mut preorder = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            preorder.push(v);
+            for e in &g[v] {
+                if e.to != parent[v] {
+                    parent[e.to] = v;
+                    depth[e.to] = depth[v] + 1;
+                    stack.push(e.to);
+                }
+            }
+        }
+        assert_eq!(preorder.len(), n);
+        let mut size = vec![1; n];
+        let mut heavy = vec![NONE; n];
+        for &v in preorder.iter().skip(1).rev() {
+            let p = parent[v];
+            size[p] += size[v];
+            if heavy[p] == NONE || size[heavy[p]] < size[v] {
+                heavy[p] = v;
+            }
+        }
+        // heavyな子を先に訪れると、heavy pathが連続した区間になります。
+        let mut head = vec![root; n];
+        let mut pos = vec![0; n];
+        let mut out = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            pos[v] = order.len();
+            out[v] = pos[v] + size[v];
+            order.push(v);
+            for e in &g[v] {
+                if e.to != parent[v] && e.to != heavy[v] {
+                    head[e.to] = e.to;
+                    stack.push(e.to);
+                }
+            }
+            if heavy[v] != NONE {
+                head[heavy[v]] = head[v];
+                stack.push(heavy[v]);
+            }
+        }
+        Self {
+            parent,
+            depth,
+            head,
+            pos,
+            out,
+            order,
+        }
+    }
+    pub fn size(&self) -> usize {
+        //! 頂点数
+        self.pos.len()
+    }
+    pub fn pos(&self, v: usize) -> usize {
+        self.pos[v]
+    }
+    pub fn vertex(&self, pos: usize) -> usize {
+        //! posの逆
+        self.order[pos]
+    }
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        match self.parent[v] {
+            NONE => None,
+            p => Some(p),
+        }
+    }
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+    pub fn edge_pos(&self, u: usize, v: usize) -> usize {
+        //! 辺(u, v)は子の側の頂点の位置に置きます。
+        if self.parent[u] == v {
+            self.pos[u]
+        } else {
+            assert_eq!(self.parent[v], u);
+            self.pos[v]
+        }
+    }
+    pub fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        //! O(log n)
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] > self.depth[self.head[v]] {
+                u = self.parent[self.head[u]];
+            } else {
+                v = self.parent[self.head[v]];
+            }
+        }
+        if self.depth[u] < self.depth[v] {
+            u
+        } else {
+            v
+        }
+    }
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.lca(u, v)]
+    }
+    pub fn path(
+        &self,
+        mut u: usize,
+        mut v: usize,
+        edge: bool,
+    ) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+        //! uからvへのパスを区間に分けます。edgeがtrueならlcaを含みません。
+        //! return: (up, down)
+        //! upはuからlcaへ登る部分で、それぞれの区間をposの降順に辿ります。
+        //! downはlcaからvへ降りる部分で、それぞれの区間をposの昇順に辿ります。
+        let edge = edge as usize;
+        let mut up = Vec::new();
+        let mut down = Vec::new();
+        while self.head[u] != self.head[v] {
+            if self.depth[self.head[u]] > self.depth[self.head[v]] {
+                up.push(self.pos[self.head[u]]..self.pos[u] + 1);
+                u = self.parent[self.head[u]];
+            } else {
+                down.push(self.pos[self.head[v]]..self.pos[v] + 1);
+                v = self.parent[self.head[v]];
+            }
+        }
+        if self.depth[u] >= self.depth[v] {
+            up.push(self.pos[v] + edge..self.pos[u] + 1);
+        } else {
+            down.push(self.pos[u] + edge..self.pos[v] + 1);
+        }
+        down.reverse();
+        (up, down)
+    }
+    pub fn subtree(&self, v: usize, edge: bool) -> Range<usize> {
+        //! vの部分木。edgeがtrueならvを含みません。
+        self.pos[v] + edge as usize..self.out[v]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn hld_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 30;
+            let edges: Vec<_> = (1..n).map(|v| (rng.gen() as usize % v, v)).collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let hld = HeavyLightDecomposition::new(&g, 0);
+            let path_to_root = |mut v: usize| {
+                let mut path = vec![v];
+                while let Some(p) = hld.parent(v) {
+                    path.push(p);
+                    v = p;
+                }
+                path
+            };
+            for u in 0..n {
+                assert_eq!(hld.vertex(hld.pos(u)), u);
+                let pu = path_to_root(u);
+                let sub: Vec<_> = (0..n).filter(|&v| path_to_root(v).contains(&u)).collect();
+                let mut got: Vec<_> = hld.subtree(u, false).map(|i| hld.vertex(i)).collect();
+                got.sort();
+                assert_eq!(got, sub);
+                for v in 0..n {
+                    let pv = path_to_root(v);
+                    let w = *pu.iter().find(|x| pv.contains(x)).unwrap();
+                    assert_eq!(hld.lca(u, v), w);
+                    let mut naive: Vec<_> = pu.iter().take_while(|&&x| x != w).copied().collect();
+                    let mut rest: Vec<_> = pv.iter().take_while(|&&x| x != w).copied().collect();
+                    naive.push(w);
+                    rest.reverse();
+                    naive.extend(rest);
+                    let (up, down) = hld.path(u, v, false);
+                    let mut path = Vec::new();
+                    for r in up {
+                        path.extend(r.rev().map(|i| hld.vertex(i)));
+                    }
+                    for r in down {
+                        path.extend(r.map(|i| hld.vertex(i)));
+                    }
+                    assert_eq!(path, naive);
+                    assert_eq!(hld.dist(u, v), naive.len() - 1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::HeavyLightDecomposition;
+use algebra::*;
+use lazy_segment_tree::LazySegmentTree;
+use segment_tree::SegmentTree;
+use std::ops::Range;
+
+fn mirror(n: usize, r: &Range<usize>) -> Range<usize> {
+    n - r.end..n - r.start
+}
+
+/// パスと部分木の積を求めるSegmentTree
+/// 非可換なモノイドのために、位置を反転させた木も持っています。
+pub struct HldSegmentTree<T: Monoid> {
+    hld: HeavyLightDecomposition,
+    seg: SegmentTree<T>,
+    rev: SegmentTree<T>,
+    edge: bool,
+}
+impl<T: Monoid + Copy> HldSegmentTree<T> {
+    pub fn new(hld: HeavyLightDecomposition, edge: bool) -> Self {
+        //! edgeがtrueなら、辺に値を乗せます。
+        let n = hld.size();
+        Self {
+            hld,
+            seg: SegmentTree::new(n),
+            rev: SegmentTree::new(n),
+            edge,
+        }
+    }
+    pub fn new_with_init(hld: HeavyLightDecomposition, init: &[T], edge: bool) -> Self {
+        //! init[v]は頂点vの値です。辺のときは、子の側の頂点に辺の値を入れてください。
+        let n = hld.size();
+        let mut data = vec![T::identity(); n];
+        for (v, &x) in init.iter().enumerate() {
+            data[hld.pos(v)] = x;
+        }
+        let seg = SegmentTree::new_with_init(&data);
+        data.reverse();
+        Self {
+            hld,
+            seg,
+            rev: SegmentTree::new_with_init(&data),
+            edge,
+        }
+    }
+    pub fn hld(&self) -> &HeavyLightDecomposition {
+        &self.hld
+    }
+    pub fn set(&mut self, v: usize, x: T) {
+        //! 辺のときはedge_posの子の側の頂点を渡してください。
+        let n = self.hld.size();
+        let p = self.hld.pos(v);
+        self.seg.set(p, x);
+        self.rev.set(n - 1 - p, x);
+    }
+    pub fn get(&self, v: usize) -> T {
+        self.seg[self.hld.pos(v)]
+    }
+    pub fn path_query(&self, u: usize, v: usize) -> T {
+        //! uからvへ順番に掛けたもの。O(log^2 n)
+        let n = self.hld.size();
+        let (up, down) = self.hld.path(u, v, self.edge);
+        let mut ret = T::identity();
+        for r in &up {
+            ret.op_from_right(self.rev.query(mirror(n, r)));
+        }
+        for r in down {
+            ret.op_from_right(self.seg.query(r));
+        }
+        ret
+    }
+    pub fn subtree_query(&self, v: usize) -> T {
+        //! posの順に掛けたもの
+        self.seg.query(self.hld.subtree(v, self.edge))
+    }
+}
+
+/// パスと部分木に作用させられるLazySegmentTree
+pub struct HldLazySegmentTree<T: Monoid, E: Monoid> {
+    hld: HeavyLightDecomposition,
+    seg: LazySegmentTree<T, E>,
+    rev: LazySegmentTree<T, E>,
+    edge: bool,
+}
+impl<T: Monoid + Copy, E: Monoid + Copy> HldLazySegmentTree<T, E> {
+    pub fn new_with_init(
+        hld: HeavyLightDecomposition,
+        init: &[T],
+        affecter: fn(T, E) -> T,
+        edge: bool,
+    ) -> Self {
+        //! init[v]は頂点vの値です。
+        let n = hld.size();
+        let mut data = vec![T::identity(); n];
+        for (v, &x) in init.iter().enumerate() {
+            data[hld.pos(v)] = x;
+        }
+        let seg = LazySegmentTree::new_with_init(&data, affecter);
+        data.reverse();
+        Self {
+            hld,
+            seg,
+            rev: LazySegmentTree::new_with_init(&data, affecter),
+            edge,
+        }
+    }
+    pub fn hld(&self) -> &HeavyLightDecomposition {
+        &self.hld
+    }
+    fn apply(&mut self, r: Range<usize>, e: E) {
+        let n = self.hld.size();
+        self.rev.update(mirror(n, &r), e);
+        self.seg.update(r, e);
+    }
+    pub fn path_apply(&mut self, u: usize, v: usize, e: E) {
+        //! O(log^2 n)
+        let (up, down) = self.hld.path(u, v, self.edge);
+        for r in up.into_iter().chain(down) {
+            self.apply(r, e);
+        }
+    }
+    pub fn subtree_apply(&mut self, v: usize, e: E) {
+        let r = self.hld.subtree(v, self.edge);
+        self.apply(r, e);
+    }
+    pub fn path_query(&mut self, u: usize, v: usize) -> T {
+        //! uからvへ順番に掛けたもの。O(log^2 n)
+        let n = self.hld.size();
+        let (up, down) = self.hld.path(u, v, self.edge);
+        let mut ret = T::identity();
+        for r in &up {
+            ret.op_from_right(self.rev.query(mirror(n, r)));
+        }
+        for r in down {
+            ret.op_from_right(self.seg.query(r));
+        }
+        ret
+    }
+    pub fn subtree_query(&mut self, v: usize) -> T {
+        let r = self.hld.subtree(v, self.edge);
+        self.seg.query(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra_struct::Ends;
+    use graph::Graph;
+    use xorshift::Xorshift256;
+    const MOD: u64 = 998244353;
+    def_monoid! {
+        derive(Copy),
+        struct Affine {
+            a: u64,
+            b: u64,
+        },
+        Affine { a: 1, b: 0 },
+        fn op(lhs: Affine, rhs: Affine) -> Affine {
+            // lhsを先に適用します。
+            Affine {
+                a: lhs.a * rhs.a % MOD,
+                b: (lhs.b * rhs.a + rhs.b) % MOD,
+            }
+        }
+    }
+    fn naive_path(parent: &[usize], u: usize, v: usize) -> Vec<usize> {
+        let up = |mut v: usize| {
+            let mut path = vec![v];
+            while v != 0 {
+                v = parent[v];
+                path.push(v);
+            }
+            path
+        };
+        let (pu, pv) = (up(u), up(v));
+        let w = *pu.iter().find(|x| pv.contains(x)).unwrap();
+        let mut path: Vec<_> = pu.iter().take_while(|&&x| x != w).copied().collect();
+        let mut rest: Vec<_> = pv.iter().take_while(|&&x| x != w).copied().collect();
+        path.push(w);
+        rest.reverse();
+        path.extend(rest);
+        path
+    }
+    #[test]
+    fn hld_segment_tree_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..30 {
+            let n = 1 + rng.gen() as usize % 30;
+            let parent: Vec<_> = (0..n)
+                .map(|v| if v == 0 { 0 } else { rng.gen() as usize % v })
+                .collect();
+            let edges: Vec<_> = (1..n).map(|v| (parent[v], v)).collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let mut val: Vec<_> = (0..n)
+                .map(|_| Affine {
+                    a: rng.gen() % MOD,
+                    b: rng.gen() % MOD,
+                })
+                .collect();
+            let hld = HeavyLightDecomposition::new(&g, 0);
+            let mut vertex = HldSegmentTree::new_with_init(hld.clone(), &val, false);
+            let mut edge = HldSegmentTree::new_with_init(hld, &val, true);
+            for _ in 0..50 {
+                let (u, v) = (rng.gen() as usize % n, rng.gen() as usize % n);
+                if rng.gen() % 3 == 1 {
+                    let x = Affine {
+                        a: rng.gen() % MOD,
+                        b: rng.gen() % MOD,
+                    };
+                    val[u] = x;
+                    vertex.set(u, x);
+                    edge.set(u, x);
+                    continue;
+                }
+                let path = naive_path(&parent, u, v);
+                let naive = path
+                    .iter()
+                    .fold(Affine::identity(), |acc, &x| Affine::op(acc, val[x]));
+                assert_eq!(vertex.path_query(u, v), naive);
+                // 辺はパス上で隣り合う頂点のうち子の方です。
+                let naive = path.windows(2).fold(Affine::identity(), |acc, w| {
+                    let c = if parent[w[0]] == w[1] && w[0] != 0 {
+                        w[0]
+                    } else {
+                        w[1]
+                    };
+                    Affine::op(acc, val[c])
+                });
+                assert_eq!(edge.path_query(u, v), naive);
+                // 部分木はposの順なので、可換なaの積だけ比べます。
+                let naive = (0..n)
+                    .filter(|&x| naive_path(&parent, x, 0).contains(&u))
+                    .fold(1, |acc, x| acc * val[x].a % MOD);
+                assert_eq!(vertex.subtree_query(u).a, naive);
+            }
+        }
+    }
+    #[test]
+    fn hld_lazy_segment_tree_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..30 {
+            let n = 1 + rng.gen() as usize % 30;
+            let parent: Vec<_> = (0..n)
+                .map(|v| if v == 0 { 0 } else { rng.gen() as usize % v })
+                .collect();
+            let edges: Vec<_> = (1..n).map(|v| (parent[v], v)).collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let mut val: Vec<i64> = (0..n).map(|_| (rng.gen() % 100) as i64).collect();
+            let init: Vec<_> = val.iter().map(|&x| Ends::leaf(x)).collect();
+            let affecter = |t: Ends, e: i64| e.act(&t);
+            let hld = HeavyLightDecomposition::new(&g, 0);
+            let mut seg = HldLazySegmentTree::new_with_init(hld, &init, affecter, false);
+            for _ in 0..50 {
+                let (u, v) = (rng.gen() as usize % n, rng.gen() as usize % n);
+                let path = naive_path(&parent, u, v);
+                match rng.gen() % 3 {
+                    0 => {
+                        let e = (rng.gen() % 10) as i64;
+                        seg.path_apply(u, v, e);
+                        for &x in &path {
+                            val[x] += e;
+                        }
+                    }
+                    1 => {
+                        let e = (rng.gen() % 10) as i64;
+                        seg.subtree_apply(u, e);
+                        for (x, y) in val.iter_mut().enumerate() {
+                            if naive_path(&parent, x, 0).contains(&u) {
+                                *y += e;
+                            }
+                        }
+                    }
+                    _ => {
+                        let got = seg.path_query(u, v);
+                        assert_eq!(got.first, Some(val[u]));
+                        assert_eq!(got.last, Some(val[v]));
+                        assert_eq!(got.sum, path.iter().map(|&x| val[x]).sum::<i64>());
+                        assert_eq!(got.len, path.len() as i64);
+                        let sum: i64 = (0..n)
+                            .filter(|&x| naive_path(&parent, x, 0).contains(&u))
+                            .map(|x| val[x])
+                            .sum();
+                        assert_eq!(seg.subtree_query(u).sum, sum);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use algebra::*;
+use std::mem::swap;
+
+const NONE: usize = !0;
+
+#[derive(Clone, Debug)]
+struct Node<T, E> {
+    left: usize,
+    right: usize,
+    parent: usize,
+    val: T,
+    sum: T,
+    /// 逆順の積
+    rsum: T,
+    lazy: E,
+    rev: bool,
+}
+
+/// Link-Cut Tree
+/// 各頂点にTを乗せて、パス上の積とパスへの作用ができます。
+/// splay_tree::SplayTreeはBoxで持った親のない木を根から位置で辿るものですが、
+/// ここでは頂点から直接splayして、splay木の根からpath-parentを辿る必要があるので、
+/// 親ポインタを持ったノードをVecに並べて別にsplayしています。
+pub struct LinkCutTree<T: Monoid, E: Monoid> {
+    nodes: Vec<Node<T, E>>,
+    affecter: fn(T, E) -> T,
+}
+impl<T: Monoid + Copy, E: Monoid + Copy> LinkCutTree<T, E> {
+    pub fn new(n: usize, affecter: fn(T, E) -> T) -> Self {
+        Self::new_with_init(&vec![T::identity(); n], affecter)
+    }
+    pub fn new_with_init(init: &[T], affecter: fn(T, E) -> T) -> Self {
+        //! 辺のない森から始まります。
+        let nodes = init
+            .iter()
+            .map(|&x| Node {
+                left: NONE,
+                right: NONE,
+                parent: NONE,
+                val: x,
+                sum: x,
+                rsum: x,
+                lazy: E::identity(),
+                rev: false,
+            })
+            .collect();
+        Self { nodes, affecter }
+    }
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+    fn is_root(&self, x: usize) -> bool {
+        //! splay木の根か
+        let p = self.nodes[x].parent;
+        p == NONE || (self.nodes[p].left != x && self.nodes[p].right != x)
+    }
+    fn sum(&self, x: usize) -> T {
+        if x == NONE {
+            T::identity()
+        } else {
+            self.nodes[x].sum
+        }
+    }
+    fn rsum(&self, x: usize) -> T {
+        if x == NONE {
+            T::identity()
+        } else {
+            self.nodes[x].rsum
+        }
+    }
+    fn update(&mut self, x: usize) {
+        let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+        let val = self.nodes[x].val;
+        self.nodes[x].sum = T::op(T::op(self.sum(l), val), self.sum(r));
+        self.nodes[x].rsum = T::op(T::op(self.rsum(r), val), self.rsum(l));
+    }
+    fn apply(&mut self, x: usize, e: E) {
+        if x == NONE {
+            return;
+        }
+        let f = self.affecter;
+        let node = &mut self.nodes[x];
+        node.val = f(node.val, e);
+        node.sum = f(node.sum, e);
+        node.rsum = f(node.rsum, e);
+        node.lazy = E::op(node.lazy, e);
+    }
+    fn toggle(&mut self, x: usize) {
+        if x == NONE {
+            return;
+        }
+        let node = &mut self.nodes[x];
+        swap(&mut node.left, &mut node.right);
+        swap(&mut node.sum, &mut node.rsum);
+        node.rev ^= true;
+    }
+    fn push(&mut self, x: usize) {
+        let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+        let e = self.nodes[x].lazy;
+        if e != E::identity() {
+            self.apply(l, e);
+            self.apply(r, e);
+            self.nodes[x].lazy = E::identity();
+        }
+        if self.nodes[x].rev {
+            self.toggle(l);
+            self.toggle(r);
+            self.nodes[x].rev = false;
+        }
+    }
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].parent;
+        let g = self.nodes[p].parent;
+        if self.nodes[p].left == x {
+            let b = self.nodes[x].right;
+            self.nodes[p].left = b;
+            if b != NONE {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].right = p;
+        } else {
+            let b = self.nodes[x].left;
+            self.nodes[p].right = b;
+            if b != NONE {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].left = p;
+        }
+        self.nodes[p].parent = x;
+        self.nodes[x].parent = g;
+        // gが別のsplay木なら、親へのポインタだけ残します。
+        if g != NONE {
+            if self.nodes[g].left == p {
+                self.nodes[g].left = x;
+            } else if self.nodes[g].right == p {
+                self.nodes[g].right = x;
+            }
+        }
+        self.update(p);
+        self.update(x);
+    }
+    fn splay(&mut self, x: usize) {
+        let mut path = vec![x];
+        let mut y = x;
+        while !self.is_root(y) {
+            y = self.nodes[y].parent;
+            path.push(y);
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_root(x) {
+            let p = self.nodes[x].parent;
+            if !self.is_root(p) {
+                let g = self.nodes[p].parent;
+                if (self.nodes[g].left == p) == (self.nodes[p].left == x) {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+    fn access(&mut self, x: usize) -> usize {
+        //! 根からxまでをひとつのsplay木にします。
+        //! return: 最後に繋いだsplay木の根 (lcaに使います)
+        let mut last = NONE;
+        let mut y = x;
+        while y != NONE {
+            self.splay(y);
+            self.nodes[y].right = last;
+            self.update(y);
+            last = y;
+            y = self.nodes[y].parent;
+        }
+        self.splay(x);
+        last
+    }
+    pub fn evert(&mut self, x: usize) {
+        //! xを根にします。
+        self.access(x);
+        self.toggle(x);
+        self.push(x);
+    }
+    pub fn root(&mut self, x: usize) -> usize {
+        self.access(x);
+        let mut y = x;
+        loop {
+            self.push(y);
+            if self.nodes[y].left == NONE {
+                break;
+            }
+            y = self.nodes[y].left;
+        }
+        self.splay(y);
+        y
+    }
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.root(u) == self.root(v)
+    }
+    pub fn link(&mut self, child: usize, parent: usize) {
+        //! childを根にしてからparentの子にします。
+        assert!(!self.connected(child, parent));
+        self.evert(child);
+        self.nodes[child].parent = parent;
+    }
+    pub fn cut(&mut self, u: usize, v: usize) {
+        //! 辺(u, v)を切ります。
+        self.evert(u);
+        self.access(v);
+        assert!(self.nodes[v].left == u && self.nodes[u].right == NONE);
+        self.nodes[v].left = NONE;
+        self.nodes[u].parent = NONE;
+        self.update(v);
+    }
+    pub fn lca(&mut self, u: usize, v: usize) -> Option<usize> {
+        //! 今の根についてのlca。非連結ならNone
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        Some(self.access(v))
+    }
+    pub fn get(&mut self, x: usize) -> T {
+        self.access(x);
+        self.nodes[x].val
+    }
+    pub fn set(&mut self, x: usize, val: T) {
+        self.access(x);
+        self.nodes[x].val = val;
+        self.update(x);
+    }
+    pub fn path_query(&mut self, u: usize, v: usize) -> T {
+        //! uからvへ順番に掛けたもの。根はuに変わります。償却O(log n)
+        self.evert(u);
+        self.access(v);
+        self.nodes[v].sum
+    }
+    pub fn path_apply(&mut self, u: usize, v: usize, e: E) {
+        //! 根はuに変わります。
+        self.evert(u);
+        self.access(v);
+        self.apply(v, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra_struct::Ends;
+    use xorshift::Xorshift256;
+    fn path(adj: &[Vec<bool>], u: usize, v: usize) -> Option<Vec<usize>> {
+        //! 森でのuからvへのパス
+        let n = adj.len();
+        let mut parent = vec![NONE; n];
+        parent[u] = u;
+        let mut stack = vec![u];
+        while let Some(x) = stack.pop() {
+            for y in 0..n {
+                if adj[x][y] && parent[y] == NONE {
+                    parent[y] = x;
+                    stack.push(y);
+                }
+            }
+        }
+        if parent[v] == NONE {
+            return None;
+        }
+        let mut ret = vec![v];
+        let mut x = v;
+        while x != u {
+            x = parent[x];
+            ret.push(x);
+        }
+        ret.reverse();
+        Some(ret)
+    }
+    #[test]
+    fn link_cut_tree_test() {
+        let mut rng = Xorshift256::new();
+        let affecter = |t: Ends, e: i64| e.act(&t);
+        for _ in 0..30 {
+            let n = 1 + rng.gen() as usize % 10;
+            let mut val: Vec<i64> = (0..n).map(|_| (rng.gen() % 100) as i64).collect();
+            let init: Vec<_> = val.iter().map(|&x| Ends::leaf(x)).collect();
+            let mut lct = LinkCutTree::new_with_init(&init, affecter);
+            let mut adj = vec![vec![false; n]; n];
+            for _ in 0..300 {
+                let (u, v) = (rng.gen() as usize % n, rng.gen() as usize % n);
+                let p = path(&adj, u, v);
+                assert_eq!(lct.connected(u, v), p.is_some());
+                match rng.gen() % 5 {
+                    0 => {
+                        if p.is_none() {
+                            lct.link(u, v);
+                            adj[u][v] = true;
+                            adj[v][u] = true;
+                        }
+                    }
+                    1 => {
+                        if adj[u][v] {
+                            lct.cut(u, v);
+                            adj[u][v] = false;
+                            adj[v][u] = false;
+                        }
+                    }
+                    2 => {
+                        if let Some(p) = p {
+                            let e = (rng.gen() % 10) as i64;
+                            lct.path_apply(u, v, e);
+                            for x in p {
+                                val[x] += e;
+                            }
+                        }
+                    }
+                    3 => {
+                        // rを根にしたときのlca
+                        let r = rng.gen() as usize % n;
+                        match (path(&adj, r, u), path(&adj, r, v)) {
+                            (Some(a), Some(b)) => {
+                                let w = a.iter().zip(&b).take_while(|(x, y)| x == y).last();
+                                lct.evert(r);
+                                assert_eq!(lct.lca(u, v), Some(*w.unwrap().0));
+                            }
+                            _ => assert_eq!(lct.lca(u, v).is_some(), p.is_some()),
+                        }
+                    }
+                    _ => {
+                        if let Some(p) = p {
+                            let got = lct.path_query(u, v);
+                            assert_eq!(got.first, Some(val[u]));
+                            assert_eq!(got.last, Some(val[v]));
+                            assert_eq!(got.sum, p.iter().map(|&x| val[x]).sum::<i64>());
+                            assert_eq!(got.len, p.len() as i64);
+                            assert_eq!(lct.get(u).sum, val[u]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
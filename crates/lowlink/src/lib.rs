@@ -0,0 +1,263 @@
+use graph::Graph;
+
+const NONE: usize = !0;
+
+/// 無向グラフの橋、関節点、二辺連結成分、二重頂点連結成分
+pub struct Lowlink {
+    ord: Vec<usize>,
+    low: Vec<usize>,
+    is_bridge: Vec<bool>,
+    is_articulation: Vec<bool>,
+    two_edge_id: Vec<usize>,
+    two_edge_groups: Vec<Vec<usize>>,
+    blocks: Vec<Vec<usize>>,
+    block_edges: Vec<Vec<usize>>,
+}
+impl Lowlink {
+    pub fn new<W>(g: &Graph<W>) -> Self {
+        //! 無向グラフを渡してください。多重辺があっても大丈夫です。O(n + m)
+        let n = g.size();
+        let mut ord = vec![NONE; n];
+        let mut low = vec![0; n];
+        let mut is_bridge = vec![false; g.edge_count()];
+        let mut is_articulation = vec![false; n];
+        let mut blocks = Vec::new();
+        let mut block_edges = Vec::new();
+        let mut now = 0;
+        let mut edge_stack = Vec::new();
+        for s in 0..n {
+            if ord[s] != NONE {
+                continue;
+            }
+            ord[s] = now;
+            low[s] = now;
+            now += 1;
+            let mut root_children = 0;
+            // (頂点, 親から来た辺のid, 次に見る辺)
+            let mut call = vec![(s, NONE, 0)];
+            while let Some(&mut (v, pe, ref mut i)) = call.last_mut() {
+                if *i < g[v].len() {
+                    let (to, id) = (g[v][*i].to, g[v][*i].id);
+                    *i += 1;
+                    if id == pe {
+                        continue;
+                    }
+                    if ord[to] == NONE {
+                        ord[to] = now;
+                        low[to] = now;
+                        now += 1;
+                        edge_stack.push((v, to, id));
+                        call.push((to, id, 0));
+                    } else if ord[to] < ord[v] {
+                        low[v] = low[v].min(ord[to]);
+                        edge_stack.push((v, to, id));
+                    }
+                    continue;
+                }
+                call.pop();
+                let p = match call.last() {
+                    Some(&(p, _, _)) => p,
+                    None => break,
+                };
+                low[p] = low[p].min(low[v]);
+                if low[v] > ord[p] {
+                    is_bridge[pe] = true;
+                }
+                if low[v] >= ord[p] {
+                    if p == s {
+                        root_children += 1;
+                    } else {
+                        is_articulation[p] = true;
+                    }
+                    // pの下がひとつのブロックになります。
+                    let mut vs = Vec::new();
+                    let mut es = Vec::new();
+                    loop {
+                        let (a, b, id) = edge_stack.pop().unwrap();
+                        vs.push(a);
+                        vs.push(b);
+                        es.push(id);
+                        if id == pe {
+                            break;
+                        }
+                    }
+                    vs.sort();
+                    vs.dedup();
+                    blocks.push(vs);
+                    block_edges.push(es);
+                }
+            }
+            if root_children >= 2 {
+                is_articulation[s] = true;
+            }
+            if root_children == 0 {
+                // 孤立点
+                blocks.push(vec![s]);
+                block_edges.push(Vec::new());
+            }
+        }
+        // 橋を通らずに行けるところが二辺連結成分です。
+        let mut two_edge_id = vec![NONE; n];
+        let mut two_edge_groups = Vec::new();
+        for s in 0..n {
+            if two_edge_id[s] != NONE {
+                continue;
+            }
+            let c = two_edge_groups.len();
+            two_edge_id[s] = c;
+            let mut group = vec![s];
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for e in &g[v] {
+                    if !is_bridge[e.id] && two_edge_id[e.to] == NONE {
+                        two_edge_id[e.to] = c;
+                        group.push(e.to);
+                        stack.push(e.to);
+                    }
+                }
+            }
+            group.sort();
+            two_edge_groups.push(group);
+        }
+        Self {
+            ord,
+            low,
+            is_bridge,
+            is_articulation,
+            two_edge_id,
+            two_edge_groups,
+            blocks,
+            block_edges,
+        }
+    }
+    pub fn ord(&self, v: usize) -> usize {
+        self.ord[v]
+    }
+    pub fn low(&self, v: usize) -> usize {
+        self.low[v]
+    }
+    pub fn is_bridge(&self, id: usize) -> bool {
+        self.is_bridge[id]
+    }
+    pub fn is_articulation_point(&self, v: usize) -> bool {
+        self.is_articulation[v]
+    }
+    pub fn bridges(&self) -> Vec<usize> {
+        //! 橋の辺id
+        (0..self.is_bridge.len())
+            .filter(|&i| self.is_bridge[i])
+            .collect()
+    }
+    pub fn articulation_points(&self) -> Vec<usize> {
+        (0..self.is_articulation.len())
+            .filter(|&v| self.is_articulation[v])
+            .collect()
+    }
+    pub fn two_edge_id(&self, v: usize) -> usize {
+        //! vが属する二辺連結成分の番号
+        self.two_edge_id[v]
+    }
+    pub fn two_edge_connected_components(&self) -> &[Vec<usize>] {
+        &self.two_edge_groups
+    }
+    pub fn biconnected_components(&self) -> &[Vec<usize>] {
+        //! 各ブロックの頂点。孤立点もひとつのブロックです。
+        &self.blocks
+    }
+    pub fn biconnected_component_edges(&self) -> &[Vec<usize>] {
+        //! 各ブロックの辺id。自己ループはどこにも入りません。
+        &self.block_edges
+    }
+    pub fn block_cut_tree(&self) -> Graph<()> {
+        //! 頂点0..nが元の頂点、n + iがi番目のブロックです。森になります。
+        let n = self.ord.len();
+        let mut edges = Vec::new();
+        for (i, b) in self.blocks.iter().enumerate() {
+            for &v in b {
+                edges.push((v, n + i));
+            }
+        }
+        Graph::undirected_unweighted(n + self.blocks.len(), &edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unionfind::Unionfind;
+    use xorshift::Xorshift256;
+    fn count_groups(
+        n: usize,
+        edges: &[(usize, usize)],
+        skip_edge: usize,
+        skip_vertex: usize,
+    ) -> usize {
+        let mut uf = Unionfind::new(n);
+        for (i, &(u, v)) in edges.iter().enumerate() {
+            if i != skip_edge && u != skip_vertex && v != skip_vertex {
+                uf.unite(u, v);
+            }
+        }
+        uf.get_num_of_groups()
+    }
+    #[test]
+    fn lowlink_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..200 {
+            let n = 1 + rng.gen() as usize % 8;
+            let m = rng.gen() as usize % 12;
+            let edges: Vec<_> = (0..m)
+                .map(|_| (rng.gen() as usize % n, rng.gen() as usize % n))
+                .collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let ll = Lowlink::new(&g);
+            let base = count_groups(n, &edges, NONE, NONE);
+            for i in 0..m {
+                assert_eq!(ll.is_bridge(i), count_groups(n, &edges, i, NONE) > base);
+            }
+            for v in 0..n {
+                // 頂点vを消すとvの分の1つが減ります。
+                let naive = count_groups(n, &edges, NONE, v) - 1 > base;
+                assert_eq!(ll.is_articulation_point(v), naive);
+            }
+            // 同じ二辺連結成分 ⇔ どの辺を1本消しても連結
+            for u in 0..n {
+                for v in 0..n {
+                    let naive = (0..=m).all(|i| {
+                        let mut uf = Unionfind::new(n);
+                        for (j, &(a, b)) in edges.iter().enumerate() {
+                            if i != j {
+                                uf.unite(a, b);
+                            }
+                        }
+                        uf.is_same_group(u, v)
+                    });
+                    assert_eq!(ll.two_edge_id(u) == ll.two_edge_id(v), naive);
+                }
+            }
+            // 自己ループ以外の辺はちょうど1つのブロックに入ります。
+            let mut cnt = vec![0; m];
+            for es in ll.biconnected_component_edges() {
+                for &i in es {
+                    cnt[i] += 1;
+                }
+            }
+            for (i, &(u, v)) in edges.iter().enumerate() {
+                assert_eq!(cnt[i], if u == v { 0 } else { 1 });
+            }
+            // 関節点 ⇔ 2つ以上のブロックに入る
+            let mut belong = vec![0; n];
+            for b in ll.biconnected_components() {
+                for &v in b {
+                    belong[v] += 1;
+                }
+            }
+            for (v, &c) in belong.iter().enumerate() {
+                assert_eq!(ll.is_articulation_point(v), c >= 2);
+            }
+            let tree = ll.block_cut_tree();
+            let tree_edges: usize = belong.iter().sum();
+            assert_eq!(tree_edges + base, tree.size());
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+
+const NONE: usize = !0;
+
+/// Hopcroft-Karp
+pub struct BipartiteMatching {
+    g: Vec<Vec<usize>>,
+    match_left: Vec<usize>,
+    match_right: Vec<usize>,
+    dist: Vec<usize>,
+}
+impl BipartiteMatching {
+    pub fn new(left: usize, right: usize) -> Self {
+        Self {
+            g: vec![Vec::new(); left],
+            match_left: vec![NONE; left],
+            match_right: vec![NONE; right],
+            dist: vec![NONE; left],
+        }
+    }
+    pub fn add_edge(&mut self, l: usize, r: usize) {
+        assert!(l < self.g.len() && r < self.match_right.len());
+        self.g[l].push(r);
+    }
+    fn bfs(&mut self) -> bool {
+        let mut queue = VecDeque::new();
+        for l in 0..self.g.len() {
+            if self.match_left[l] == NONE {
+                self.dist[l] = 0;
+                queue.push_back(l);
+            } else {
+                self.dist[l] = NONE;
+            }
+        }
+        let mut found = false;
+        while let Some(l) = queue.pop_front() {
+            for &r in &self.g[l] {
+                let l2 = self.match_right[r];
+                if l2 == NONE {
+                    found = true;
+                } else if self.dist[l2] == NONE {
+                    self.dist[l2] = self.dist[l] + 1;
+                    queue.push_back(l2);
+                }
+            }
+        }
+        found
+    }
+    fn dfs(&mut self, l: usize) -> bool {
+        for i in 0..self.g[l].len() {
+            let r = self.g[l][i];
+            let l2 = self.match_right[r];
+            if l2 == NONE || (self.dist[l2] == self.dist[l] + 1 && self.dfs(l2)) {
+                self.match_left[l] = r;
+                self.match_right[r] = l;
+                return true;
+            }
+        }
+        self.dist[l] = NONE;
+        false
+    }
+    pub fn matching(&mut self) -> Vec<(usize, usize)> {
+        //! O(m sqrt(n))
+        //! return: (左, 右)の組
+        while self.bfs() {
+            for l in 0..self.g.len() {
+                if self.match_left[l] == NONE {
+                    self.dfs(l);
+                }
+            }
+        }
+        (0..self.g.len())
+            .filter(|&l| self.match_left[l] != NONE)
+            .map(|l| (l, self.match_left[l]))
+            .collect()
+    }
+    pub fn min_vertex_cover(&mut self) -> (Vec<usize>, Vec<usize>) {
+        //! Königの定理。return: (左の頂点, 右の頂点)
+        self.matching();
+        // マッチしていない左から交互路で行ける頂点
+        let mut visited_left = vec![false; self.g.len()];
+        let mut visited_right = vec![false; self.match_right.len()];
+        let mut stack: Vec<usize> = (0..self.g.len())
+            .filter(|&l| self.match_left[l] == NONE)
+            .collect();
+        for &l in &stack {
+            visited_left[l] = true;
+        }
+        while let Some(l) = stack.pop() {
+            for &r in &self.g[l] {
+                if visited_right[r] {
+                    continue;
+                }
+                visited_right[r] = true;
+                let l2 = self.match_right[r];
+                if l2 != NONE && !visited_left[l2] {
+                    visited_left[l2] = true;
+                    stack.push(l2);
+                }
+            }
+        }
+        (
+            (0..self.g.len()).filter(|&l| !visited_left[l]).collect(),
+            (0..self.match_right.len())
+                .filter(|&r| visited_right[r])
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn bipartite_matching_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..100 {
+            let left = 1 + rng.gen() as usize % 6;
+            let right = 1 + rng.gen() as usize % 6;
+            let m = rng.gen() as usize % 12;
+            let edges: Vec<_> = (0..m)
+                .map(|_| (rng.gen() as usize % left, rng.gen() as usize % right))
+                .collect();
+            let mut bm = BipartiteMatching::new(left, right);
+            for &(l, r) in &edges {
+                bm.add_edge(l, r);
+            }
+            let pairs = bm.matching();
+            let naive = (0..1usize << m)
+                .filter(|&b| {
+                    let (mut ul, mut ur) = (0, 0);
+                    for (i, &(l, r)) in edges.iter().enumerate() {
+                        if b >> i & 1 == 1 {
+                            if ul >> l & 1 == 1 || ur >> r & 1 == 1 {
+                                return false;
+                            }
+                            ul |= 1 << l;
+                            ur |= 1 << r;
+                        }
+                    }
+                    true
+                })
+                .map(|b| b.count_ones() as usize)
+                .max()
+                .unwrap();
+            assert_eq!(pairs.len(), naive);
+            for &(l, r) in &pairs {
+                assert!(edges.contains(&(l, r)));
+            }
+            let (cl, cr) = bm.min_vertex_cover();
+            assert_eq!(cl.len() + cr.len(), naive);
+            for &(l, r) in &edges {
+                assert!(cl.contains(&l) || cr.contains(&r));
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+const NONE: usize = !0;
+
+/// Edmondsの花アルゴリズム
+pub struct GeneralMatching {
+    g: Vec<Vec<usize>>,
+    mate: Vec<usize>,
+    parent: Vec<usize>,
+    base: Vec<usize>,
+    used: Vec<bool>,
+    blossom: Vec<bool>,
+}
+impl GeneralMatching {
+    pub fn new(n: usize) -> Self {
+        Self {
+            g: vec![Vec::new(); n],
+            mate: vec![NONE; n],
+            parent: vec![NONE; n],
+            base: (0..n).collect(),
+            used: vec![false; n],
+            blossom: vec![false; n],
+        }
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        assert!(u < self.g.len() && v < self.g.len());
+        if u != v {
+            self.g[u].push(v);
+            self.g[v].push(u);
+        }
+    }
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut on_path = vec![false; self.g.len()];
+        loop {
+            a = self.base[a];
+            on_path[a] = true;
+            if self.mate[a] == NONE {
+                break;
+            }
+            a = self.parent[self.mate[a]];
+        }
+        loop {
+            b = self.base[b];
+            if on_path[b] {
+                return b;
+            }
+            b = self.parent[self.mate[b]];
+        }
+    }
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize) {
+        while self.base[v] != b {
+            self.blossom[self.base[v]] = true;
+            self.blossom[self.base[self.mate[v]]] = true;
+            self.parent[v] = child;
+            child = self.mate[v];
+            v = self.parent[self.mate[v]];
+        }
+    }
+    fn find_path(&mut self, root: usize) -> usize {
+        //! return: 増加路の終点
+        let n = self.g.len();
+        for v in 0..n {
+            self.used[v] = false;
+            self.parent[v] = NONE;
+            self.base[v] = v;
+        }
+        self.used[root] = true;
+        let mut queue = vec![root];
+        let mut head = 0;
+        while head < queue.len() {
+            let v = queue[head];
+            head += 1;
+            for i in 0..self.g[v].len() {
+                let to = self.g[v][i];
+                if self.base[v] == self.base[to] || self.mate[v] == to {
+                    continue;
+                }
+                if to == root || (self.mate[to] != NONE && self.parent[self.mate[to]] != NONE) {
+                    // 奇閉路を縮約します。
+                    let b = self.lca(v, to);
+                    for x in self.blossom.iter_mut() {
+                        *x = false;
+                    }
+                    self.mark_path(v, b, to);
+                    self.mark_path(to, b, v);
+                    for u in 0..n {
+                        if self.blossom[self.base[u]] {
+                            self.base[u] = b;
+                            if !self.used[u] {
+                                self.used[u] = true;
+                                queue.push(u);
+                            }
+                        }
+                    }
+                } else if self.parent[to] == NONE {
+                    self.parent[to] = v;
+                    if self.mate[to] == NONE {
+                        return to;
+                    }
+                    let to2 = self.mate[to];
+                    self.used[to2] = true;
+                    queue.push(to2);
+                }
+            }
+        }
+        NONE
+    }
+    pub fn matching(&mut self) -> Vec<(usize, usize)> {
+        //! O(n^3)
+        //! return: (u, v) (u < v) の組
+        let n = self.g.len();
+        for root in 0..n {
+            if self.mate[root] != NONE {
+                continue;
+            }
+            let mut v = self.find_path(root);
+            while v != NONE {
+                let pv = self.parent[v];
+                let ppv = self.mate[pv];
+                self.mate[v] = pv;
+                self.mate[pv] = v;
+                v = ppv;
+            }
+        }
+        (0..n)
+            .filter(|&v| self.mate[v] != NONE && v < self.mate[v])
+            .map(|v| (v, self.mate[v]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn general_matching_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..200 {
+            let n = 1 + rng.gen() as usize % 8;
+            let m = rng.gen() as usize % 14;
+            let edges: Vec<_> = (0..m)
+                .map(|_| (rng.gen() as usize % n, rng.gen() as usize % n))
+                .collect();
+            let mut gm = GeneralMatching::new(n);
+            for &(u, v) in &edges {
+                gm.add_edge(u, v);
+            }
+            let pairs = gm.matching();
+            let naive = (0..1usize << m)
+                .filter(|&b| {
+                    let mut used = 0;
+                    for (i, &(u, v)) in edges.iter().enumerate() {
+                        if b >> i & 1 == 1 {
+                            if u == v || used >> u & 1 == 1 || used >> v & 1 == 1 {
+                                return false;
+                            }
+                            used |= 1 << u | 1 << v;
+                        }
+                    }
+                    true
+                })
+                .map(|b| b.count_ones() as usize)
+                .max()
+                .unwrap();
+            assert_eq!(pairs.len(), naive);
+            let mut used = vec![false; n];
+            for &(u, v) in &pairs {
+                assert!(edges.contains(&(u, v)) || edges.contains(&(v, u)));
+                assert!(!used[u] && !used[v]);
+                used[u] = true;
+                used[v] = true;
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// 1.42ではi64::MAXがまだないのでstd::i64::MAXを使います。
+#[allow(clippy::legacy_numeric_constants)]
+const INF: i64 = std::i64::MAX;
+
+pub fn hungarian(a: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    //! n×m (n <= m) のコスト行列で、各行に違う列を割り当てたときの最小コスト
+    //! 最大化したいときは符号を反転してください。O(n^2 m)
+    //! return: (コスト, 各行に割り当てた列)
+    let n = a.len();
+    if n == 0 {
+        return (0, Vec::new());
+    }
+    let m = a[0].len();
+    assert!(n <= m);
+    // 1-indexedで、列0は番兵です。
+    let mut u = vec![0; n + 1];
+    let mut v = vec![0; m + 1];
+    let mut p = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![INF; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = INF;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let cur = a[i0 - 1][j - 1] - u[i0] - v[j];
+                if cur < minv[j] {
+                    minv[j] = cur;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        // 増加路に沿って入れ替えます。
+        while j0 != 0 {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+        }
+    }
+    let mut assign = vec![0; n];
+    for j in 1..=m {
+        if p[j] != 0 {
+            assign[p[j] - 1] = j - 1;
+        }
+    }
+    (-v[0], assign)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    fn brute(a: &[Vec<i64>], i: usize, used: &mut Vec<bool>) -> i64 {
+        if i == a.len() {
+            return 0;
+        }
+        let mut ret = INF;
+        for j in 0..used.len() {
+            if !used[j] {
+                used[j] = true;
+                ret = ret.min(a[i][j] + brute(a, i + 1, used));
+                used[j] = false;
+            }
+        }
+        ret
+    }
+    #[test]
+    fn hungarian_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..100 {
+            let n = 1 + rng.gen() as usize % 5;
+            let m = n + rng.gen() as usize % 3;
+            let a: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..m).map(|_| (rng.gen() % 100) as i64 - 50).collect())
+                .collect();
+            let (cost, assign) = hungarian(&a);
+            assert_eq!(cost, brute(&a, 0, &mut vec![false; m]));
+            let mut cols = assign.clone();
+            cols.sort();
+            cols.dedup();
+            assert_eq!(cols.len(), n);
+            assert_eq!((0..n).map(|i| a[i][assign[i]]).sum::<i64>(), cost);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+mod bipartite_matching;
+mod general_matching;
+mod hungarian;
+pub use bipartite_matching::*;
+pub use general_matching::*;
+pub use hungarian::*;
@@ -0,0 +1,222 @@
+use algebra::Zero;
+use std::ops::Sub;
+use unionfind::RollbackUnionfind;
+
+const NONE: usize = !0;
+
+/// 全体に足す遅延つきのskew heap。ノードiは辺iです。
+struct SkewHeap<W> {
+    key: Vec<(W, usize)>,
+    lazy: Vec<W>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+}
+impl<W: Zero + Ord + Copy> SkewHeap<W> {
+    fn push_down(&mut self, a: usize) {
+        let d = self.lazy[a];
+        if d == W::zero() {
+            return;
+        }
+        self.key[a].0 += d;
+        for &c in &[self.left[a], self.right[a]] {
+            if c != NONE {
+                self.lazy[c] += d;
+            }
+        }
+        self.lazy[a] = W::zero();
+    }
+    fn merge(&mut self, mut a: usize, mut b: usize) -> usize {
+        // 右の背骨に沿ってマージしてから、帰りがけに左右を入れ替えます。
+        let mut spine = Vec::new();
+        while a != NONE && b != NONE {
+            self.push_down(a);
+            self.push_down(b);
+            if self.key[a] > self.key[b] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            spine.push(a);
+            a = self.right[a];
+        }
+        let mut t = if a == NONE { b } else { a };
+        while let Some(x) = spine.pop() {
+            self.right[x] = self.left[x];
+            self.left[x] = t;
+            t = x;
+        }
+        t
+    }
+    fn pop(&mut self, a: usize) -> usize {
+        self.push_down(a);
+        self.merge(self.left[a], self.right[a])
+    }
+}
+
+pub fn minimum_arborescence<W: Zero + Ord + Copy + Sub<Output = W>>(
+    n: usize,
+    root: usize,
+    edges: &[(usize, usize, W)],
+) -> Option<(W, Vec<Option<usize>>)> {
+    //! 有向辺(from, to, weight)に対するrootを根とした最小有向全域木 (Chu-Liu/Edmonds)
+    //! O(m log m)
+    //! return: (重みの和, 各頂点に入る辺の番号)
+    //! 根から行けない頂点があればNoneです。
+    let mut uf = RollbackUnionfind::new(n);
+    let mut heap = SkewHeap {
+        key: edges.iter().enumerate().map(|(i, e)| (e.2, i)).collect(),
+        lazy: vec![W::zero(); edges.len()],
+        left: vec![NONE; edges.len()],
+        right: vec![NONE; edges.len()],
+    };
+    let mut top = vec![NONE; n];
+    for (i, &(_, v, _)) in edges.iter().enumerate() {
+        top[v] = heap.merge(top[v], i);
+    }
+    let mut total = W::zero();
+    let mut seen = vec![NONE; n];
+    let mut path = vec![0; n];
+    let mut queue = vec![0; n];
+    let mut incoming = vec![NONE; n];
+    let mut cycles = Vec::new();
+    seen[root] = root;
+    for s in 0..n {
+        let mut u = s;
+        let mut qi = 0;
+        while seen[u] == NONE {
+            let h = top[u];
+            if h == NONE {
+                return None;
+            }
+            heap.push_down(h);
+            let (w, e) = heap.key[h];
+            heap.lazy[h] = W::zero() - w;
+            top[u] = heap.pop(h);
+            queue[qi] = e;
+            path[qi] = u;
+            qi += 1;
+            seen[u] = s;
+            total += w;
+            u = uf.find_root(edges[e].0);
+            if seen[u] == s {
+                // 閉路を縮約します。
+                let mut cycle = NONE;
+                let end = qi;
+                let time = uf.snapshot();
+                loop {
+                    qi -= 1;
+                    let w = path[qi];
+                    cycle = heap.merge(cycle, top[w]);
+                    if uf.unite(u, w).is_none() {
+                        break;
+                    }
+                }
+                u = uf.find_root(u);
+                top[u] = cycle;
+                seen[u] = NONE;
+                cycles.push((u, time, queue[qi..end].to_vec()));
+            }
+        }
+        for &e in &queue[..qi] {
+            incoming[uf.find_root(edges[e].1)] = e;
+        }
+    }
+    // 縮約を戻しながら入る辺を復元します。
+    for (u, time, cycle) in cycles.into_iter().rev() {
+        uf.rollback(time);
+        let e = incoming[u];
+        for f in cycle {
+            incoming[uf.find_root(edges[f].1)] = f;
+        }
+        incoming[uf.find_root(edges[e].1)] = e;
+    }
+    let incoming = incoming
+        .into_iter()
+        .enumerate()
+        .map(|(v, e)| if v == root { None } else { Some(e) })
+        .collect();
+    Some((total, incoming))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    fn brute(n: usize, root: usize, edges: &[(usize, usize, i64)]) -> Option<i64> {
+        fn dfs(
+            v: usize,
+            n: usize,
+            root: usize,
+            edges: &[(usize, usize, i64)],
+            par: &mut Vec<usize>,
+        ) -> Option<i64> {
+            if v == n {
+                // 全頂点から根に辿り着けるか
+                for s in 0..n {
+                    let mut u = s;
+                    for _ in 0..n {
+                        if u != root {
+                            u = par[u];
+                        }
+                    }
+                    if u != root {
+                        return None;
+                    }
+                }
+                return Some(0);
+            }
+            if v == root {
+                return dfs(v + 1, n, root, edges, par);
+            }
+            let mut ret = None;
+            for &(u, to, w) in edges {
+                if to == v && u != v {
+                    par[v] = u;
+                    if let Some(x) = dfs(v + 1, n, root, edges, par) {
+                        ret = Some(ret.map_or(x + w, |y: i64| y.min(x + w)));
+                    }
+                }
+            }
+            ret
+        }
+        dfs(0, n, root, edges, &mut vec![root; n])
+    }
+    #[test]
+    fn arborescence_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..200 {
+            let n = 1 + rng.gen() as usize % 5;
+            let m = rng.gen() as usize % 10;
+            let root = rng.gen() as usize % n;
+            let edges: Vec<_> = (0..m)
+                .map(|_| {
+                    let u = rng.gen() as usize % n;
+                    let v = rng.gen() as usize % n;
+                    (u, v, (rng.gen() % 10) as i64 - 3)
+                })
+                .collect();
+            let res = minimum_arborescence(n, root, &edges);
+            assert_eq!(res.as_ref().map(|x| x.0), brute(n, root, &edges));
+            if let Some((total, incoming)) = res {
+                let mut sum = 0;
+                for (v, &e) in incoming.iter().enumerate() {
+                    match e {
+                        None => assert_eq!(v, root),
+                        Some(e) => {
+                            assert_eq!(edges[e].1, v);
+                            sum += edges[e].2;
+                        }
+                    }
+                }
+                assert_eq!(sum, total);
+                for s in 0..n {
+                    let mut u = s;
+                    for _ in 0..n {
+                        if let Some(e) = incoming[u] {
+                            u = edges[e].0;
+                        }
+                    }
+                    assert_eq!(u, root);
+                }
+            }
+        }
+    }
+}
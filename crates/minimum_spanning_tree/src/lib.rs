@@ -0,0 +1,139 @@
+use algebra::Zero;
+use graph::Graph;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use unionfind::Unionfind;
+mod arborescence;
+pub use arborescence::*;
+
+const NONE: usize = !0;
+
+pub fn kruskal<W: Zero + Ord + Copy>(n: usize, edges: &[(usize, usize, W)]) -> (W, Vec<usize>) {
+    //! O(m log m)
+    //! return: (重みの和, 使った辺の番号)
+    //! 連結でなければ最小全域森になります。
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by_key(|&i| edges[i].2);
+    let mut uf = Unionfind::new(n);
+    let mut total = W::zero();
+    let mut used = Vec::with_capacity(n.saturating_sub(1));
+    for i in order {
+        let (u, v, w) = edges[i];
+        if uf.unite(u, v).is_some() {
+            total += w;
+            used.push(i);
+        }
+    }
+    (total, used)
+}
+
+pub fn prim<W: Zero + Ord + Copy>(g: &Graph<W>) -> (W, Vec<usize>) {
+    //! 無向グラフを渡してください。O(m log m)
+    //! return: (重みの和, 使った辺のid)
+    let n = g.size();
+    let mut visited = vec![false; n];
+    let mut total = W::zero();
+    let mut used = Vec::with_capacity(n.saturating_sub(1));
+    let mut heap = BinaryHeap::new();
+    for s in 0..n {
+        if visited[s] {
+            continue;
+        }
+        visited[s] = true;
+        heap.extend(g[s].iter().map(|e| Reverse((e.weight, e.id, e.to))));
+        while let Some(Reverse((w, id, v))) = heap.pop() {
+            if visited[v] {
+                continue;
+            }
+            visited[v] = true;
+            total += w;
+            used.push(id);
+            for e in &g[v] {
+                if !visited[e.to] {
+                    heap.push(Reverse((e.weight, e.id, e.to)));
+                }
+            }
+        }
+    }
+    (total, used)
+}
+
+pub fn boruvka<W: Zero + Ord + Copy>(n: usize, edges: &[(usize, usize, W)]) -> (W, Vec<usize>) {
+    //! O(m log n)
+    //! return: (重みの和, 使った辺の番号)
+    let mut uf = Unionfind::new(n);
+    let mut total = W::zero();
+    let mut used = Vec::with_capacity(n.saturating_sub(1));
+    loop {
+        // 各成分から出る最小の辺。同じ重みは番号で比べないと閉路ができます。
+        let mut best = vec![NONE; n];
+        for (i, &(u, v, w)) in edges.iter().enumerate() {
+            let (ru, rv) = (uf.find_root(u), uf.find_root(v));
+            if ru == rv {
+                continue;
+            }
+            for &r in &[ru, rv] {
+                if best[r] == NONE || (w, i) < (edges[best[r]].2, best[r]) {
+                    best[r] = i;
+                }
+            }
+        }
+        let mut updated = false;
+        for i in best {
+            if i == NONE {
+                continue;
+            }
+            let (u, v, w) = edges[i];
+            if uf.unite(u, v).is_some() {
+                total += w;
+                used.push(i);
+                updated = true;
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+    (total, used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    fn check_forest(n: usize, edges: &[(usize, usize, i64)], used: &[usize]) -> i64 {
+        let mut uf = Unionfind::new(n);
+        for &(u, v, _) in edges {
+            uf.unite(u, v);
+        }
+        assert_eq!(used.len(), n - uf.get_num_of_groups());
+        let mut uf = Unionfind::new(n);
+        for &i in used {
+            assert!(uf.unite(edges[i].0, edges[i].1).is_some());
+        }
+        used.iter().map(|&i| edges[i].2).sum()
+    }
+    #[test]
+    fn mst_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 15;
+            let m = rng.gen() as usize % 40;
+            let edges: Vec<_> = (0..m)
+                .map(|_| {
+                    let u = rng.gen() as usize % n;
+                    let v = rng.gen() as usize % n;
+                    (u, v, (rng.gen() % 10) as i64 - 3)
+                })
+                .collect();
+            let (w1, used1) = kruskal(n, &edges);
+            let (w2, used2) = prim(&Graph::undirected(n, &edges));
+            let (w3, used3) = boruvka(n, &edges);
+            assert_eq!(w1, w2);
+            assert_eq!(w1, w3);
+            assert_eq!(check_forest(n, &edges, &used1), w1);
+            assert_eq!(check_forest(n, &edges, &used2), w1);
+            assert_eq!(check_forest(n, &edges, &used3), w1);
+        }
+    }
+}
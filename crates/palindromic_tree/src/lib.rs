@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+struct Node {
+    next: BTreeMap<u8, usize>,
+    link: usize,
+    len: isize,
+    cnt: usize,
+    depth: usize,
+}
+impl Node {
+    fn new(len: isize, link: usize, depth: usize) -> Self {
+        Self {
+            next: BTreeMap::new(),
+            link,
+            len,
+            cnt: 0,
+            depth,
+        }
+    }
+}
+
+/// eertree
+/// 0番が長さ-1, 1番が長さ0の根です。
+pub struct PalindromicTree {
+    nodes: Vec<Node>,
+    s: Vec<u8>,
+    last: usize,
+    total: u64,
+}
+impl PalindromicTree {
+    pub fn new(s: &[u8]) -> Self {
+        let mut ret = Self {
+            nodes: vec![Node::new(-1, 0, 0), Node::new(0, 0, 0)],
+            s: Vec::with_capacity(s.len()),
+            last: 1,
+            total: 0,
+        };
+        for &c in s {
+            ret.push(c);
+        }
+        ret
+    }
+    fn find(&self, mut v: usize) -> usize {
+        //! s[i - len - 1] == s[i] となる接尾辞回文まで辿ります。
+        let i = self.s.len() as isize - 1;
+        loop {
+            let j = i - self.nodes[v].len - 1;
+            if j >= 0 && self.s[j as usize] == self.s[i as usize] {
+                return v;
+            }
+            v = self.nodes[v].link;
+        }
+    }
+    pub fn push(&mut self, c: u8) -> bool {
+        //! return: 新しい回文ができたか
+        self.s.push(c);
+        let p = self.find(self.last);
+        let created = if let Some(&x) = self.nodes[p].next.get(&c) {
+            self.last = x;
+            false
+        } else {
+            let len = self.nodes[p].len + 2;
+            let link = if len == 1 {
+                1
+            } else {
+                let q = self.find(self.nodes[p].link);
+                self.nodes[q].next[&c]
+            };
+            let v = self.nodes.len();
+            self.nodes
+                .push(Node::new(len, link, self.nodes[link].depth + 1));
+            self.nodes[p].next.insert(c, v);
+            self.last = v;
+            true
+        };
+        self.nodes[self.last].cnt += 1;
+        self.total += self.nodes[self.last].depth as u64;
+        created
+    }
+    pub fn size(&self) -> usize {
+        //! 根2つを含むノード数
+        self.nodes.len()
+    }
+    pub fn distinct_palindromes(&self) -> usize {
+        self.nodes.len() - 2
+    }
+    pub fn palindromic_substrings(&self) -> u64 {
+        //! 回文である部分文字列の個数 (位置が違えば区別します)
+        self.total
+    }
+    pub fn len(&self, node: usize) -> isize {
+        self.nodes[node].len
+    }
+    pub fn link(&self, node: usize) -> usize {
+        self.nodes[node].link
+    }
+    pub fn next(&self, node: usize, c: u8) -> Option<usize> {
+        self.nodes[node].next.get(&c).copied()
+    }
+    pub fn last(&self) -> usize {
+        //! 今の文字列の最長接尾辞回文
+        self.last
+    }
+    pub fn occurrences(&self) -> Vec<usize> {
+        //! 各ノードの回文の出現回数
+        let mut ret: Vec<usize> = self.nodes.iter().map(|v| v.cnt).collect();
+        // linkは必ず先に作られたノードを指します。
+        for v in (2..self.nodes.len()).rev() {
+            ret[self.nodes[v].link] += ret[v];
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use xorshift::Xorshift256;
+    #[test]
+    fn palindromic_tree_test() {
+        let mut rng = Xorshift256::new();
+        for n in 0..40 {
+            let s: Vec<u8> = (0..n).map(|_| b'a' + (rng.gen() % 2) as u8).collect();
+            let tree = PalindromicTree::new(&s);
+            let mut occ = HashMap::new();
+            for i in 0..n {
+                for j in i + 1..=n {
+                    let t = &s[i..j];
+                    if t.iter().eq(t.iter().rev()) {
+                        *occ.entry(t).or_insert(0) += 1;
+                    }
+                }
+            }
+            assert_eq!(tree.distinct_palindromes(), occ.len());
+            assert_eq!(
+                tree.palindromic_substrings(),
+                occ.values().sum::<usize>() as u64
+            );
+            let cnt = tree.occurrences();
+            for (t, &c) in occ.iter() {
+                // 中心から辿ります。
+                let h = t.len() / 2;
+                let mut v = if t.len() % 2 == 1 {
+                    tree.next(0, t[h]).unwrap()
+                } else {
+                    1
+                };
+                for &c in &t[h + t.len() % 2..] {
+                    v = tree.next(v, c).unwrap();
+                }
+                assert_eq!(tree.len(v), t.len() as isize);
+                assert_eq!(cnt[v], c);
+            }
+        }
+    }
+}
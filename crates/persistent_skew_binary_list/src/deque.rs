@@ -0,0 +1,455 @@
+use crate::SkewBinaryList;
+use std::iter::FromIterator;
+use std::mem::replace;
+use std::ops::Range;
+use std::rc::Rc;
+
+// 固め直しを1回の操作で何個ずつ進めるか
+const STEP: usize = 4;
+
+/// 永続deque
+/// 両端に足した分と、真ん中の固めた部分に分けてSkewBinaryListで持ちます。
+/// 両端が真ん中より長くなったら固め直しを始めて、操作のたびに少しずつ進めます。
+/// 版ごとに進み具合を持つので、同じ版から何度分岐させても各操作は最悪O(log n)です。
+#[derive(Clone)]
+pub struct PersistentDeque<T: Clone> {
+    front: Side<T>,
+    back: Side<T>,
+    // 真ん中はfrozenの[lo, hi)です。
+    frozen: Rc<Frozen<T>>,
+    lo: usize,
+    hi: usize,
+    job: Option<Job<T>>,
+}
+impl<T: Clone> PersistentDeque<T> {
+    pub fn new() -> Self {
+        Self::from_halves(SkewBinaryList::new(), 0, SkewBinaryList::new(), 0)
+    }
+    fn from_halves(
+        front: SkewBinaryList<T>,
+        front_len: usize,
+        back: SkewBinaryList<T>,
+        back_len: usize,
+    ) -> Self {
+        let n = front_len + back_len;
+        Self {
+            front: Side::new(),
+            back: Side::new(),
+            frozen: Rc::new(Frozen {
+                front: Side::new(),
+                mid: Halves {
+                    front,
+                    back,
+                    front_len,
+                    back_len,
+                },
+                mid_range: 0..n,
+                back: Side::new(),
+            }),
+            lo: 0,
+            hi: n,
+            job: None,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.front.len + (self.hi - self.lo) + self.back.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn push_front(&self, val: T) -> Self {
+        let mut ret = self.clone();
+        ret.front = self.front.push(val);
+        ret.step()
+    }
+    pub fn push_back(&self, val: T) -> Self {
+        let mut ret = self.clone();
+        ret.back = self.back.push(val);
+        ret.step()
+    }
+    pub fn pop_front(&self) -> Option<Self> {
+        //! 空ならNone
+        let mut ret = self.clone();
+        if self.front.len > 0 {
+            ret.front = self.front.pop();
+        } else if self.lo < self.hi {
+            ret.lo += 1;
+        } else if self.back.len > 0 {
+            // 後ろの列の深い方を捨てます。
+            ret.back.len -= 1;
+        } else {
+            return None;
+        }
+        Some(ret.step())
+    }
+    pub fn pop_back(&self) -> Option<Self> {
+        //! 空ならNone
+        let mut ret = self.clone();
+        if self.back.len > 0 {
+            ret.back = self.back.pop();
+        } else if self.lo < self.hi {
+            ret.hi -= 1;
+        } else if self.front.len > 0 {
+            ret.front.len -= 1;
+        } else {
+            return None;
+        }
+        Some(ret.step())
+    }
+    pub fn front(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.access(0))
+        }
+    }
+    pub fn back(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.access(self.len() - 1))
+        }
+    }
+    pub fn access(&self, idx: usize) -> &T {
+        //! O(log n)
+        assert!(idx < self.len());
+        if idx < self.front.len {
+            return self.front.list.access(idx);
+        }
+        let idx = idx - self.front.len;
+        if idx < self.hi - self.lo {
+            self.frozen.access(self.lo + idx)
+        } else {
+            let idx = idx - (self.hi - self.lo);
+            self.back.list.access(self.back.len - 1 - idx)
+        }
+    }
+    pub fn update(&self, idx: usize, val: T) -> Self {
+        //! O(log n)
+        assert!(idx < self.len());
+        let mut ret = self.clone();
+        if idx < self.front.len {
+            ret.front.list = self.front.list.update(idx, val);
+            return ret;
+        }
+        let idx = idx - self.front.len;
+        if idx < self.hi - self.lo {
+            // 固め直しの途中なら、もうコピーした方も書き換えます。
+            let i = self.lo + idx;
+            if let Some(ref mut job) = ret.job {
+                if job.fl <= i && i < job.mid {
+                    job.nf = job.nf.update(i - job.fl, val.clone());
+                } else if job.mid <= i && i < job.bh {
+                    job.nb = job.nb.update(job.bh - 1 - i, val.clone());
+                }
+            }
+            ret.frozen = Rc::new(self.frozen.update(i, val));
+        } else {
+            let idx = idx - (self.hi - self.lo);
+            ret.back.list = self.back.list.update(self.back.len - 1 - idx, val);
+        }
+        ret
+    }
+    pub fn iter(&self) -> DequeIter<'_, T> {
+        DequeIter {
+            deque: self,
+            range: 0..self.len(),
+        }
+    }
+    fn step(mut self) -> Self {
+        // 固め直しを少し進めます。
+        let mut job = match self.job.take() {
+            Some(job) => job,
+            // 両端が真ん中より長くなったら始めます。
+            None if self.front.phys + self.back.phys > self.hi - self.lo => self.start(),
+            None => return self,
+        };
+        for _ in 0..STEP {
+            if job.fl > self.lo {
+                job.fl -= 1;
+                job.nf = job.nf.push_front(self.frozen.access(job.fl).clone());
+            } else if job.bh < self.hi {
+                job.nb = job.nb.push_front(self.frozen.access(job.bh).clone());
+                job.bh += 1;
+            } else {
+                self.finish(job);
+                return self;
+            }
+        }
+        self.job = Some(job);
+        self
+    }
+    fn start(&mut self) -> Job<T> {
+        // 今の中身を全部frozenに入れて、両端は空から始めます。
+        let mid = self.frozen.mid.clone();
+        let start = self.frozen.mid_range.start;
+        let frozen = Frozen {
+            front: replace(&mut self.front, Side::new()),
+            mid,
+            mid_range: start + self.lo..start + self.hi,
+            back: replace(&mut self.back, Side::new()),
+        };
+        self.lo = 0;
+        self.hi = frozen.len();
+        self.frozen = Rc::new(frozen);
+        let mid = self.hi / 2;
+        Job {
+            mid,
+            fl: mid,
+            nf: SkewBinaryList::new(),
+            bh: mid,
+            nb: SkewBinaryList::new(),
+        }
+    }
+    fn finish(&mut self, job: Job<T>) {
+        let n = job.bh - job.fl;
+        self.frozen = Rc::new(Frozen {
+            front: Side::new(),
+            mid: Halves {
+                front: job.nf,
+                back: job.nb,
+                front_len: job.mid - job.fl,
+                back_len: job.bh - job.mid,
+            },
+            mid_range: 0..n,
+            back: Side::new(),
+        });
+        self.lo -= job.fl;
+        self.hi -= job.fl;
+    }
+}
+impl<T: Clone> Default for PersistentDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Clone> FromIterator<T> for PersistentDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let data: Vec<T> = iter.into_iter().collect();
+        let mid = data.len() / 2;
+        Self::from_halves(
+            data[..mid].iter().cloned().collect(),
+            mid,
+            data[mid..].iter().rev().cloned().collect(),
+            data.len() - mid,
+        )
+    }
+}
+impl<'a, T: Clone> IntoIterator for &'a PersistentDeque<T> {
+    type Item = &'a T;
+    type IntoIter = DequeIter<'a, T>;
+    fn into_iter(self) -> DequeIter<'a, T> {
+        self.iter()
+    }
+}
+
+/// 前から順に返すやつ。1個O(log n)です。
+pub struct DequeIter<'a, T: Clone> {
+    deque: &'a PersistentDeque<T>,
+    range: Range<usize>,
+}
+impl<'a, T: Clone> Iterator for DequeIter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let i = self.range.next()?;
+        Some(self.deque.access(i))
+    }
+}
+
+/// 端に足していく列。深い方のphys - len個は捨てたものです。
+#[derive(Clone)]
+struct Side<T: Clone> {
+    list: SkewBinaryList<T>,
+    len: usize,
+    phys: usize,
+}
+impl<T: Clone> Side<T> {
+    fn new() -> Self {
+        Self {
+            list: SkewBinaryList::new(),
+            len: 0,
+            phys: 0,
+        }
+    }
+    fn push(&self, val: T) -> Self {
+        Self {
+            list: self.list.push_front(val),
+            len: self.len + 1,
+            phys: self.phys + 1,
+        }
+    }
+    fn pop(&self) -> Self {
+        Self {
+            list: self.list.pop_front().unwrap(),
+            len: self.len - 1,
+            phys: self.phys - 1,
+        }
+    }
+}
+
+/// frontと、backを逆にしたものをつなげた列
+#[derive(Clone)]
+struct Halves<T: Clone> {
+    front: SkewBinaryList<T>,
+    back: SkewBinaryList<T>,
+    front_len: usize,
+    back_len: usize,
+}
+impl<T: Clone> Halves<T> {
+    fn access(&self, i: usize) -> &T {
+        if i < self.front_len {
+            self.front.access(i)
+        } else {
+            self.back.access(self.front_len + self.back_len - 1 - i)
+        }
+    }
+    fn update(&self, i: usize, val: T) -> Self {
+        let mut ret = self.clone();
+        if i < self.front_len {
+            ret.front = self.front.update(i, val);
+        } else {
+            ret.back = self
+                .back
+                .update(self.front_len + self.back_len - 1 - i, val);
+        }
+        ret
+    }
+}
+
+/// 固め直しを始めたときの中身。front + midのmid_rangeの部分 + backを逆にしたもの
+struct Frozen<T: Clone> {
+    front: Side<T>,
+    mid: Halves<T>,
+    mid_range: Range<usize>,
+    back: Side<T>,
+}
+impl<T: Clone> Frozen<T> {
+    fn len(&self) -> usize {
+        self.front.len + self.mid_range.len() + self.back.len
+    }
+    fn access(&self, i: usize) -> &T {
+        if i < self.front.len {
+            return self.front.list.access(i);
+        }
+        let i = i - self.front.len;
+        if i < self.mid_range.len() {
+            self.mid.access(self.mid_range.start + i)
+        } else {
+            let i = i - self.mid_range.len();
+            self.back.list.access(self.back.len - 1 - i)
+        }
+    }
+    fn update(&self, i: usize, val: T) -> Self {
+        let mut front = self.front.clone();
+        let mut mid = self.mid.clone();
+        let mut back = self.back.clone();
+        if i < self.front.len {
+            front.list = self.front.list.update(i, val);
+        } else if i - self.front.len < self.mid_range.len() {
+            mid = self
+                .mid
+                .update(self.mid_range.start + i - self.front.len, val);
+        } else {
+            let i = i - self.front.len - self.mid_range.len();
+            back.list = self.back.list.update(self.back.len - 1 - i, val);
+        }
+        Self {
+            front,
+            mid,
+            mid_range: self.mid_range.clone(),
+            back,
+        }
+    }
+}
+
+/// 固め直しの進み具合
+/// frozenの[fl, mid)をnfに、[mid, bh)を逆順でnbにコピーしてあります。
+#[derive(Clone)]
+struct Job<T: Clone> {
+    mid: usize,
+    fl: usize,
+    nf: SkewBinaryList<T>,
+    bh: usize,
+    nb: SkewBinaryList<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::*;
+    use std::collections::VecDeque;
+    #[test]
+    fn persistent_deque_test() {
+        let mut rng = thread_rng();
+        let mut versions = vec![(PersistentDeque::new(), VecDeque::new())];
+        for _ in 0..2000 {
+            let (dq, naive) = versions[rng.gen_range(0, versions.len())].clone();
+            let mut naive: VecDeque<i32> = naive;
+            let dq = match rng.gen_range(0, 5) {
+                0 => {
+                    let x = rng.gen();
+                    naive.push_front(x);
+                    dq.push_front(x)
+                }
+                1 => {
+                    let x = rng.gen();
+                    naive.push_back(x);
+                    dq.push_back(x)
+                }
+                2 => match naive.pop_front() {
+                    Some(_) => dq.pop_front().unwrap(),
+                    None => {
+                        assert!(dq.pop_front().is_none());
+                        continue;
+                    }
+                },
+                3 => match naive.pop_back() {
+                    Some(_) => dq.pop_back().unwrap(),
+                    None => {
+                        assert!(dq.pop_back().is_none());
+                        continue;
+                    }
+                },
+                _ if !naive.is_empty() => {
+                    let k = rng.gen_range(0, naive.len());
+                    let x = rng.gen();
+                    naive[k] = x;
+                    dq.update(k, x)
+                }
+                _ => continue,
+            };
+            assert_eq!(dq.len(), naive.len());
+            assert_eq!(dq.front(), naive.front());
+            assert_eq!(dq.back(), naive.back());
+            for (i, x) in naive.iter().enumerate() {
+                assert_eq!(dq.access(i), x);
+            }
+            versions.push((dq, naive));
+        }
+        for (dq, naive) in &versions {
+            assert!(dq.iter().eq(naive.iter()));
+        }
+        let dq: PersistentDeque<_> = (0..10).collect();
+        assert!(dq.iter().copied().eq(0..10));
+    }
+    #[test]
+    fn queue_test() {
+        // 後ろに足して前から取るのを繰り返しても、中身は正しく保たれます。
+        let mut rng = thread_rng();
+        let mut dq = PersistentDeque::new();
+        let mut naive = VecDeque::new();
+        for i in 0..3000 {
+            if rng.gen_range(0, 3) > 0 {
+                dq = dq.push_back(i);
+                naive.push_back(i);
+            } else if let Some(next) = dq.pop_front() {
+                dq = next;
+                naive.pop_front();
+            }
+            assert_eq!(dq.len(), naive.len());
+            assert_eq!(dq.front(), naive.front());
+            assert_eq!(dq.back(), naive.back());
+        }
+        assert!(dq.iter().eq(naive.iter()));
+    }
+}
@@ -1,4 +1,7 @@
+use std::iter::FromIterator;
 use std::rc::Rc;
+mod deque;
+pub use deque::*;
 
 #[derive(Clone)]
 struct SkewList<T: Clone>(Option<Rc<SkewListNode<T>>>);
@@ -69,7 +72,7 @@ impl<T: Clone> SkewTree<T> {
     pub fn update(&self, size: usize, idx: usize, val: T) -> Self {
         let inner = self.0.as_ref().unwrap();
         if idx == 0 {
-            Self::new_with_leaf(val)
+            Self::new_with_node(val, inner.left.clone(), inner.right.clone())
         } else {
             assert!(size != 1);
             let rem = idx - 1;
@@ -84,7 +87,7 @@ impl<T: Clone> SkewTree<T> {
                 Self::new_with_node(
                     inner.value.clone(),
                     inner.left.clone(),
-                    inner.right.update(ch_size, rem, val),
+                    inner.right.update(ch_size, rem - ch_size, val),
                 )
             }
         }
@@ -97,11 +100,51 @@ struct SkewTreeNode<T: Clone> {
     right: SkewTree<T>,
 }
 
+/// 永続のランダムアクセスリスト
+/// 先頭の追加と削除がO(1)、アクセスと更新がO(log n)です。cloneはO(1)です。
+#[derive(Clone)]
 pub struct SkewBinaryList<T: Clone>(SkewList<T>);
 impl<T: Clone> SkewBinaryList<T> {
     pub fn new() -> Self {
         Self(SkewList::none())
     }
+    pub fn len(&self) -> usize {
+        //! O(log n)
+        let mut ret = 0;
+        let mut now = &self.0;
+        while let Some(ref node) = now.0 {
+            ret += node.size;
+            now = &node.next;
+        }
+        ret
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 .0.is_none()
+    }
+    pub fn head(&self) -> Option<&T> {
+        self.0
+             .0
+            .as_ref()
+            .map(|node| &node.root.0.as_ref().unwrap().value)
+    }
+    pub fn pop_front(&self) -> Option<Self> {
+        //! 先頭を除いたもの。空ならNone
+        let node = self.0 .0.as_ref()?;
+        if node.size == 1 {
+            return Some(Self(node.next.clone()));
+        }
+        // 根を外すと半分の大きさの木が2本残ります。
+        let tree = node.root.0.as_ref().unwrap();
+        let half = node.size / 2;
+        let rest = SkewList::new_with_node(half, tree.right.clone(), node.next.clone());
+        Some(Self(SkewList::new_with_node(half, tree.left.clone(), rest)))
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: &self.0,
+            stack: Vec::new(),
+        }
+    }
     pub fn update(&self, idx: usize, val: T) -> Self {
         Self(self.0.update(idx, val))
     }
@@ -134,11 +177,60 @@ impl<T: Clone> SkewBinaryList<T> {
     }
 }
 
+impl<T: Clone> Default for SkewBinaryList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<T: Clone> FromIterator<T> for SkewBinaryList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let data: Vec<T> = iter.into_iter().collect();
+        let mut ret = Self::new();
+        for x in data.into_iter().rev() {
+            ret = ret.push_front(x);
+        }
+        ret
+    }
+}
+impl<'a, T: Clone> IntoIterator for &'a SkewBinaryList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// 前から順に返すやつ
+pub struct Iter<'a, T: Clone> {
+    list: &'a SkewList<T>,
+    // 今の木で、これから行きがけ順に見る部分木
+    stack: Vec<&'a SkewTreeNode<T>>,
+}
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        if self.stack.is_empty() {
+            let node = self.list.0.as_ref()?;
+            self.stack.push(node.root.0.as_ref().unwrap());
+            self.list = &node.next;
+        }
+        let now = self.stack.pop().unwrap();
+        if let Some(ref right) = now.right.0 {
+            self.stack.push(right);
+        }
+        if let Some(ref left) = now.left.0 {
+            self.stack.push(left);
+        }
+        Some(&now.value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rand::*;
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn skew_binary_list_test() {
         let mut rng = thread_rng();
         let n = 1<<10;
@@ -151,4 +243,31 @@ mod tests {
             assert_eq!(*sbl.access(i), seq[i]);
         }
     }
+    #[test]
+    fn pop_front_test() {
+        let mut rng = thread_rng();
+        let n = 300;
+        let seq = (0..n).map(|_| rng.gen()).collect::<Vec<i32>>();
+        let sbl: SkewBinaryList<i32> = seq.iter().copied().collect();
+        assert_eq!(sbl.iter().copied().collect::<Vec<_>>(), seq);
+        let mut versions = vec![sbl];
+        for i in 0..n {
+            let now = &versions[i];
+            assert_eq!(now.len(), n - i);
+            assert_eq!(now.head(), Some(&seq[i]));
+            versions.push(now.pop_front().unwrap());
+        }
+        assert!(versions[n].is_empty());
+        assert!(versions[n].pop_front().is_none());
+        // 古い版も壊れていません。
+        for (i, v) in versions.iter().enumerate() {
+            assert!(v.iter().eq(seq[i..].iter()));
+            let k = rng.gen_range(0, n + 1);
+            if k < n - i {
+                let u = v.update(k, -1);
+                assert_eq!(*u.access(k), -1);
+                assert_eq!(*v.access(k), seq[i + k]);
+            }
+        }
+    }
 }
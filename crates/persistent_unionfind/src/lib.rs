@@ -0,0 +1,90 @@
+use persistent_skew_binary_list::SkewBinaryList;
+
+/// 完全永続Unionfind
+/// uniteは新しい版を返すので、どの版からでも枝分かれできます。cloneはO(1)です。
+/// 経路圧縮はせず、union by sizeだけなので各操作O(log^2 n)です。
+#[derive(Clone)]
+pub struct PersistentUnionfind {
+    par: SkewBinaryList<i32>,
+    size: usize,
+    group_count: usize,
+}
+impl PersistentUnionfind {
+    pub fn new(n: usize) -> Self {
+        PersistentUnionfind {
+            par: (0..n).map(|_| -1).collect(),
+            size: n,
+            group_count: n,
+        }
+    }
+    pub fn find_root(&self, mut x: usize) -> usize {
+        assert!(x < self.size);
+        while *self.par.access(x) >= 0 {
+            x = *self.par.access(x) as usize;
+        }
+        x
+    }
+    pub fn unite(&self, x: usize, y: usize) -> Self {
+        //! xとyをつないだ新しい版。もともと同じならそのままです。
+        assert!(x < self.size && y < self.size);
+        let mut rx = self.find_root(x);
+        let mut ry = self.find_root(y);
+        if rx == ry {
+            return self.clone();
+        }
+        let (mut px, mut py) = (*self.par.access(rx), *self.par.access(ry));
+        if px > py {
+            std::mem::swap(&mut rx, &mut ry);
+            std::mem::swap(&mut px, &mut py);
+        }
+        PersistentUnionfind {
+            par: self.par.update(rx, px + py).update(ry, rx as i32),
+            size: self.size,
+            group_count: self.group_count - 1,
+        }
+    }
+    pub fn is_same_group(&self, x: usize, y: usize) -> bool {
+        assert!(x < self.size && y < self.size);
+        self.find_root(x) == self.find_root(y)
+    }
+    pub fn get_group_size(&self, x: usize) -> usize {
+        (-*self.par.access(self.find_root(x))) as usize
+    }
+    pub fn get_num_of_groups(&self) -> usize {
+        self.group_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unionfind::Unionfind;
+    use xorshift::Xorshift256;
+    #[test]
+    fn persistent_unionfind_test() {
+        let mut rng = Xorshift256::new();
+        let n = 30;
+        // 各版と、そこまでにつないだ辺
+        let mut versions = vec![(PersistentUnionfind::new(n), vec![])];
+        for _ in 0..500 {
+            let i = rng.gen() as usize % versions.len();
+            let (x, y) = (rng.gen() as usize % n, rng.gen() as usize % n);
+            let uf = versions[i].0.unite(x, y);
+            let mut edges = versions[i].1.clone();
+            edges.push((x, y));
+            versions.push((uf, edges));
+        }
+        for (uf, edges) in &versions {
+            let mut naive = Unionfind::new(n);
+            for &(x, y) in edges {
+                naive.unite(x, y);
+            }
+            assert_eq!(uf.get_num_of_groups(), naive.get_num_of_groups());
+            for x in 0..n {
+                assert_eq!(uf.get_group_size(x), naive.get_group_size(x));
+                let y = rng.gen() as usize % n;
+                assert_eq!(uf.is_same_group(x, y), naive.is_same_group(x, y));
+            }
+        }
+    }
+}
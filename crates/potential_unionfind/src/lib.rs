@@ -1,17 +1,33 @@
 // ATTENTION: NOT VERIFIED
 use algebra::*;
+
+/// try_mergeの結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeResult {
+    /// もともと同じ成分で、条件は今までのものと矛盾しません。
+    Consistent,
+    /// 違う成分をつなぎました。新しい根です。
+    Merged(usize),
+    /// もともと同じ成分で、条件が今までのものと矛盾します。
+    Contradiction,
+}
+
+/// ポテンシャルつきUnionfind
+/// 各頂点にGの元p(v)があって、merge(x, y, w)は p(y) = p(x) * w という条件です。
+/// Gは可換でなくてもいいです。
 #[derive(Debug)]
-pub struct WeightedUnionfind<M: Abel> {
+pub struct WeightedUnionfind<G: Group> {
     par: Box<[i32]>,
-    diff_weight: Box<[M]>,
+    /// p(v) = p(親) * diff_weight[v]
+    diff_weight: Box<[G]>,
     group_count: usize,
 }
-impl<G: Abel> WeightedUnionfind<G> {
+impl<G: Group> WeightedUnionfind<G> {
     pub fn new(n: usize) -> Self {
         WeightedUnionfind {
             par: vec![-1; n].into_boxed_slice(),
             diff_weight: vec![G::identity(); n].into_boxed_slice(),
-            group_count: 0,
+            group_count: n,
         }
     }
     fn find_root(&mut self, x: usize) -> usize {
@@ -20,25 +36,42 @@ impl<G: Abel> WeightedUnionfind<G> {
         } else {
             let p = self.par[x] as usize;
             let r = self.find_root(p);
-            self.diff_weight[x].op_from_right(self.diff_weight[p].clone());
+            self.diff_weight[x].op_from_left(self.diff_weight[p].clone());
             self.par[x] = r as i32;
             r
         }
     }
     pub fn weight(&mut self, x: usize) -> G {
+        //! p(根)^-1 * p(x)
         self.find_root(x);
         self.diff_weight[x].clone()
     }
     pub fn diff(&mut self, x: usize, y: usize) -> G {
-        G::op(self.weight(y), self.weight(x).inv())
+        //! p(x)^-1 * p(y)。同じ成分である必要があります。
+        G::op(self.weight(x).inv(), self.weight(y))
     }
     pub fn merge(&mut self, x: usize, y: usize, w: G) -> Option<usize> {
-        let mut w = G::op(G::op(w, self.weight(x)), self.weight(y).inv());
+        //! return: new root
+        //! 同じ成分のときは何もしません。矛盾を知りたいときはtry_mergeを使います。
+        match self.try_merge(x, y, w) {
+            MergeResult::Merged(r) => Some(r),
+            _ => None,
+        }
+    }
+    pub fn try_merge(&mut self, x: usize, y: usize, w: G) -> MergeResult {
+        //! p(y) = p(x) * w という条件を足します。
+        let (wx, wy) = (self.weight(x), self.weight(y));
         let mut x = self.find_root(x);
         let mut y = self.find_root(y);
         if x == y {
-            return None;
+            return if G::op(wx.inv(), wy) == w {
+                MergeResult::Consistent
+            } else {
+                MergeResult::Contradiction
+            };
         }
+        // p(根y) = p(根x) * wx * w * wy^-1
+        let mut w = G::op(G::op(wx, w), wy.inv());
         if self.par[x] > self.par[y] {
             std::mem::swap(&mut x, &mut y);
             w = w.inv();
@@ -46,17 +79,93 @@ impl<G: Abel> WeightedUnionfind<G> {
         self.par[x] += self.par[y];
         self.par[y] = x as i32;
         self.diff_weight[y] = w;
-        Some(x)
+        self.group_count -= 1;
+        MergeResult::Merged(x)
+    }
+    pub fn is_same_group(&mut self, x: usize, y: usize) -> bool {
+        self.find_root(x) == self.find_root(y)
+    }
+    pub fn get_group_size(&mut self, x: usize) -> usize {
+        let r = self.find_root(x);
+        (-self.par[r]) as usize
+    }
+    pub fn get_num_of_groups(&self) -> usize {
+        self.group_count
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use xorshift::Xorshift256;
     #[test]
     fn test() {
         let mut uf = WeightedUnionfind::new(10);
         uf.merge(0, 1, 10);
         assert_eq!(uf.diff(0, 1), 10);
+        assert_eq!(uf.try_merge(1, 2, 5), MergeResult::Merged(0));
+        assert_eq!(uf.try_merge(0, 2, 15), MergeResult::Consistent);
+        assert_eq!(uf.try_merge(2, 0, 15), MergeResult::Contradiction);
+        assert_eq!(uf.get_num_of_groups(), 8);
+    }
+    const P: u64 = 7;
+    // t -> a * t + b (mod P)。opは左を先に作用させる合成です。
+    def_monoid! {
+        derive(Copy),
+        struct Affine(u64, u64),
+        Affine(1, 0),
+        fn op(lhs: Affine, rhs: Affine) -> Affine {
+            Affine(rhs.0 * lhs.0 % P, (rhs.0 * lhs.1 + rhs.1) % P)
+        }
+    }
+    impl Quasigroup for Affine {
+        fn inv(self) -> Self {
+            // a^-1 = a^(P-2)
+            let mut a = 1;
+            for _ in 0..P - 2 {
+                a = a * self.0 % P;
+            }
+            Affine(a, (P - self.1) * a % P)
+        }
+    }
+    #[test]
+    fn non_commutative_test() {
+        let mut rng = Xorshift256::new();
+        let mut random = || Affine(1 + rng.gen() % (P - 1), rng.gen() % P);
+        let n = 20;
+        let p: Vec<Affine> = (0..n).map(|_| random()).collect();
+        let truth = |x: usize, y: usize| Affine::op(p[x].inv(), p[y]);
+        let mut uf = WeightedUnionfind::new(n);
+        let mut comp: Vec<usize> = (0..n).collect();
+        for i in 0..300 {
+            let (x, y) = ((i * 7) % n, random().1 as usize * 3 % n);
+            if comp[x] == comp[y] {
+                let w = random();
+                let expected = if w == truth(x, y) {
+                    MergeResult::Consistent
+                } else {
+                    MergeResult::Contradiction
+                };
+                assert_eq!(uf.try_merge(x, y, w), expected);
+            } else {
+                match uf.try_merge(x, y, truth(x, y)) {
+                    MergeResult::Merged(_) => {}
+                    r => panic!("{:?}", r),
+                }
+                let (cx, cy) = (comp[x], comp[y]);
+                for c in comp.iter_mut() {
+                    if *c == cy {
+                        *c = cx;
+                    }
+                }
+            }
+            for x in 0..n {
+                for y in 0..n {
+                    if comp[x] == comp[y] {
+                        assert_eq!(uf.diff(x, y), truth(x, y));
+                    }
+                }
+            }
+        }
     }
 }
@@ -1,81 +1,149 @@
-use algebra::Monoid;
+use algebra::{Act, Monoid};
 use my_range::MyRange;
 use std::cmp::Ordering;
-use std::mem::replace;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem::{replace, swap};
 use std::ops::Range;
-pub struct RBST<T: Monoid>(Option<Box<Node<T>>>);
-impl<T: Monoid> RBST<T> {
+pub mod ordered_multiset;
+mod persistent;
+pub use ordered_multiset::OrderedMultiset;
+pub use persistent::*;
+// Eは区間に作用させるもので、いらなければ()です。
+pub struct RBST<T, E = ()>(Option<Box<Node<T, E>>>);
+impl<T, E> RBST<T, E> {
     #[inline(always)]
     pub fn none() -> Self {
         Self(None)
     }
-    pub fn build(data: &[T], range: Range<usize>) -> Self {
-        match range.len() {
-            0 => Self::none(),
-            1 => Self(Some(Box::new(Node::new(data[range.start].clone())))),
-            x => {
-                let i = Self::rand() as usize % x + range.start;
-                let mut ret = Node {
-                    left: Self::build(data, range.start..i),
-                    right: Self::build(data, i + 1..range.end),
-                    size: 1,
-                    data: data[i].clone(),
-                    sum: T::identity(),
-                };
-                ret.update();
-                Self(Some(Box::new(ret)))
-            }
-        }
-    }
     #[inline(always)]
     pub fn size(&self) -> usize {
-        self.as_ref().size
-    }
-    pub fn access(&self, k: usize) -> &T {
-        assert!(!self.is_none());
-        let rank = self.as_ref().left.size();
-        match k.cmp(&rank) {
-            Ordering::Equal => &self.as_ref().data,
-            Ordering::Less => self.as_ref().left.access(k),
-            Ordering::Greater => self.as_ref().right.access(k - rank - 1),
+        match self.0 {
+            Some(ref x) => x.size,
+            None => 0,
         }
     }
-    pub fn merge(x: Self, y: Self) -> Self {
+    // pushとupdateだけ差し替えられるmergeとsplitです。
+    fn merge_by<P, U>(x: Self, y: Self, push: P, update: U) -> Self
+    where
+        P: Fn(&mut Node<T, E>) + Copy,
+        U: Fn(&mut Node<T, E>) + Copy,
+    {
         match (x, y) {
             (Self(None), y) => y,
             (x, Self(None)) => x,
             (mut x, mut y) => {
-                if Self::rand() % ((x.size() + y.size()) as u64) < x.size() as u64 {
-                    x.as_mut().right = Self::merge(replace(&mut x.as_mut().right, Self::none()), y);
-                    x.as_mut().update();
+                if rand() % ((x.size() + y.size()) as u64) < x.size() as u64 {
+                    push(x.as_mut());
+                    x.as_mut().right = Self::merge_by(
+                        replace(&mut x.as_mut().right, Self::none()),
+                        y,
+                        push,
+                        update,
+                    );
+                    update(x.as_mut());
                     x
                 } else {
-                    y.as_mut().left = Self::merge(x, replace(&mut y.as_mut().left, Self::none()));
-                    y.as_mut().update();
+                    push(y.as_mut());
+                    y.as_mut().left = Self::merge_by(
+                        x,
+                        replace(&mut y.as_mut().left, Self::none()),
+                        push,
+                        update,
+                    );
+                    update(y.as_mut());
                     y
                 }
             }
         }
     }
-    pub fn split(self, k: usize) -> (Self, Self) {
+    fn split_by<P, U>(self, k: usize, push: P, update: U) -> (Self, Self)
+    where
+        P: Fn(&mut Node<T, E>) + Copy,
+        U: Fn(&mut Node<T, E>) + Copy,
+    {
         match self {
             Self(None) => (Self::none(), Self::none()),
             mut slf => {
+                push(slf.as_mut());
                 if k <= slf.as_ref().left.size() {
-                    let x = replace(&mut slf.as_mut().left, Self::none()).split(k);
+                    let x = replace(&mut slf.as_mut().left, Self::none()).split_by(k, push, update);
                     slf.as_mut().left = x.1;
-                    slf.as_mut().update();
+                    update(slf.as_mut());
                     (x.0, slf)
                 } else {
-                    let x = replace(&mut slf.as_mut().right, Self::none())
-                        .split(k - slf.as_ref().left.size() - 1);
+                    let x = replace(&mut slf.as_mut().right, Self::none()).split_by(
+                        k - slf.as_ref().left.size() - 1,
+                        push,
+                        update,
+                    );
                     slf.as_mut().right = x.0;
-                    slf.as_mut().update();
+                    update(slf.as_mut());
                     (slf, x.1)
                 }
             }
         }
     }
+    #[inline(always)]
+    fn as_ref(&self) -> &Node<T, E> {
+        self.0.as_ref().unwrap()
+    }
+    #[inline(always)]
+    fn as_mut(&mut self) -> &mut Node<T, E> {
+        self.0.as_mut().unwrap()
+    }
+    #[inline(always)]
+    fn unwrap(self) -> Box<Node<T, E>> {
+        self.0.unwrap()
+    }
+}
+impl<T: Monoid, E: Act<T>> RBST<T, E> {
+    pub fn build(data: &[T], range: Range<usize>) -> Self {
+        match range.len() {
+            0 => Self::none(),
+            1 => Self(Some(Box::new(Node::new(data[range.start].clone())))),
+            x => {
+                let i = rand() as usize % x + range.start;
+                let mut ret = Node::new(data[i].clone());
+                ret.left = Self::build(data, range.start..i);
+                ret.right = Self::build(data, i + 1..range.end);
+                ret.update();
+                Self(Some(Box::new(ret)))
+            }
+        }
+    }
+    pub fn get(&self, mut k: usize) -> T {
+        //! k番目の値。遅延している作用と反転は辿りながら反映します。
+        assert!(k < self.size());
+        let mut now = self;
+        let mut e = E::identity();
+        let mut rev = false;
+        loop {
+            let node = now.as_ref();
+            let (first, second) = if rev {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            let rank = first.size();
+            now = match k.cmp(&rank) {
+                Ordering::Equal => return e.act(&node.data),
+                Ordering::Less => first,
+                Ordering::Greater => {
+                    k -= rank + 1;
+                    second
+                }
+            };
+            e = E::op(node.lazy.clone(), e);
+            rev ^= node.rev;
+        }
+    }
+    pub fn merge(x: Self, y: Self) -> Self {
+        Self::merge_by(x, y, Node::push, Node::update)
+    }
+    pub fn split(self, k: usize) -> (Self, Self) {
+        self.split_by(k, Node::push, Node::update)
+    }
     pub fn query(&mut self, range: Range<usize>) -> T {
         assert!((0..self.size()).contains_range(&range));
         let sel = replace(self, Self::none());
@@ -98,70 +166,358 @@ impl<T: Monoid> RBST<T> {
         *self = Self::merge(l, r);
         del.unwrap().data
     }
-    #[inline(always)]
-    fn as_ref(&self) -> &Box<Node<T>> {
-        self.0.as_ref().unwrap()
-    }
-    #[inline(always)]
-    fn as_mut(&mut self) -> &mut Box<Node<T>> {
-        self.0.as_mut().unwrap()
+    pub fn apply(&mut self, range: Range<usize>, e: E) {
+        //! range内の全部にeを作用させます。
+        assert!((0..self.size()).contains_range(&range));
+        let sel = replace(self, Self::none());
+        let (a, b) = sel.split(range.start);
+        let (mut b, c) = b.split(range.end - range.start);
+        b.apply_all(&e);
+        *self = Self::merge(Self::merge(a, b), c);
     }
-    #[inline(always)]
-    fn unwrap(self) -> Box<Node<T>> {
-        self.0.unwrap()
+    pub fn reverse(&mut self, range: Range<usize>) {
+        assert!((0..self.size()).contains_range(&range));
+        let sel = replace(self, Self::none());
+        let (a, b) = sel.split(range.start);
+        let (mut b, c) = b.split(range.end - range.start);
+        b.toggle();
+        *self = Self::merge(Self::merge(a, b), c);
     }
-    #[inline(always)]
-    fn is_none(&self) -> bool {
-        self.0.is_none()
+    pub fn iter(&self) -> Iter<'_, T, E> {
+        let mut ret = Iter { stack: Vec::new() };
+        ret.push_spine(self, E::identity(), false);
+        ret
     }
     fn sum(&self) -> T {
         if let Some(ref x) = self.0 {
-            x.sum.clone()
+            x.sums.as_ref().unwrap().0.clone()
+        } else {
+            T::identity()
+        }
+    }
+    fn rsum(&self) -> T {
+        if let Some(ref x) = self.0 {
+            x.sums.as_ref().unwrap().1.clone()
         } else {
             T::identity()
         }
     }
-    fn rand() -> u64 {
-        static mut RAND_X: u64 = 123456789;
-        static mut RAND_Y: u64 = 987654321;
-        static mut RAND_Z: u64 = 1000000007;
-        static mut RAND_W: u64 = 1145141919;
-        unsafe {
-            let t = RAND_X ^ (RAND_X << 11);
-            RAND_X = RAND_Y;
-            RAND_Y = RAND_Z;
-            RAND_Z = RAND_W;
-            RAND_W = (RAND_W ^ (RAND_W >> 19)) ^ (t ^ (t >> 8));
-            RAND_W
+    fn apply_all(&mut self, e: &E) {
+        if let Some(ref mut x) = self.0 {
+            x.data = e.act(&x.data);
+            if let Some((ref mut sum, ref mut rsum)) = x.sums {
+                *sum = e.act(sum);
+                *rsum = e.act(rsum);
+            }
+            x.lazy = E::op(x.lazy.clone(), e.clone());
+        }
+    }
+    fn toggle(&mut self) {
+        if let Some(ref mut x) = self.0 {
+            swap(&mut x.left, &mut x.right);
+            if let Some((ref mut sum, ref mut rsum)) = x.sums {
+                swap(sum, rsum);
+            }
+            x.rev ^= true;
+        }
+    }
+}
+fn rand() -> u64 {
+    static mut RAND_X: u64 = 123456789;
+    static mut RAND_Y: u64 = 987654321;
+    static mut RAND_Z: u64 = 1000000007;
+    static mut RAND_W: u64 = 1145141919;
+    unsafe {
+        let t = RAND_X ^ (RAND_X << 11);
+        RAND_X = RAND_Y;
+        RAND_Y = RAND_Z;
+        RAND_Z = RAND_W;
+        RAND_W = (RAND_W ^ (RAND_W >> 19)) ^ (t ^ (t >> 8));
+        RAND_W
+    }
+}
+// 値だけを持つ木 (OrderedMultiset) 用。積も作用もないので大きさだけ直します。
+impl<T> RBST<T> {
+    pub(crate) fn leaf(val: T) -> Self {
+        Self(Some(Box::new(Node {
+            left: Self::none(),
+            right: Self::none(),
+            size: 1,
+            data: val,
+            sums: None,
+            lazy: (),
+            rev: false,
+        })))
+    }
+    pub(crate) fn merge_plain(x: Self, y: Self) -> Self {
+        Self::merge_by(x, y, |_| {}, Node::update_size)
+    }
+    pub(crate) fn split_plain(self, k: usize) -> (Self, Self) {
+        self.split_by(k, |_| {}, Node::update_size)
+    }
+}
+impl<T: Monoid> RBST<T> {
+    pub fn access(&self, mut k: usize) -> &T {
+        //! 作用がないときはそのまま参照を返せます。反転は辿りながら反映します。
+        assert!(k < self.size());
+        let mut now = self;
+        let mut rev = false;
+        loop {
+            let node = now.as_ref();
+            let (first, second) = if rev {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            let rank = first.size();
+            now = match k.cmp(&rank) {
+                Ordering::Equal => return &node.data,
+                Ordering::Less => first,
+                Ordering::Greater => {
+                    k -= rank + 1;
+                    second
+                }
+            };
+            rev ^= node.rev;
         }
     }
 }
-struct Node<T: Monoid> {
-    left: RBST<T>,
-    right: RBST<T>,
+struct Node<T, E> {
+    left: RBST<T, E>,
+    right: RBST<T, E>,
     size: usize,
     data: T,
-    sum: T,
+    /// (積, 逆順の積)。値だけを持つ木ではNone
+    sums: Option<(T, T)>,
+    lazy: E,
+    rev: bool,
+}
+impl<T, E> Node<T, E> {
+    fn update_size(&mut self) {
+        self.size = self.left.size() + self.right.size() + 1;
+    }
 }
-impl<T: Monoid> Node<T> {
+impl<T: Monoid, E: Act<T>> Node<T, E> {
     fn new(val: T) -> Self {
         Self {
             left: RBST::none(),
             right: RBST::none(),
             size: 1,
             data: val.clone(),
-            sum: val,
+            sums: Some((val.clone(), val)),
+            lazy: E::identity(),
+            rev: false,
         }
     }
     fn update(&mut self) {
-        self.size = self.left.size() + self.right.size() + 1;
-        self.sum = T::op(T::op(self.left.sum(), self.data.clone()), self.right.sum());
+        self.update_size();
+        let sum = T::op(T::op(self.left.sum(), self.data.clone()), self.right.sum());
+        let rsum = T::op(
+            T::op(self.right.rsum(), self.data.clone()),
+            self.left.rsum(),
+        );
+        self.sums = Some((sum, rsum));
+    }
+    fn push(&mut self) {
+        if self.lazy != E::identity() {
+            let e = replace(&mut self.lazy, E::identity());
+            self.left.apply_all(&e);
+            self.right.apply_all(&e);
+        }
+        if self.rev {
+            self.left.toggle();
+            self.right.toggle();
+            self.rev = false;
+        }
+    }
+}
+/// 中順に値を返すやつ。遅延している作用と反転はその場で反映します。
+pub struct Iter<'a, T: Monoid, E: Act<T>> {
+    // (ノード, 上から降ってくる作用, 反転しているか)
+    stack: Vec<(&'a Node<T, E>, E, bool)>,
+}
+impl<'a, T: Monoid, E: Act<T>> Iter<'a, T, E> {
+    fn push_spine(&mut self, mut now: &'a RBST<T, E>, mut e: E, mut rev: bool) {
+        while let Some(ref node) = now.0 {
+            let child_e = E::op(node.lazy.clone(), e.clone());
+            let child_rev = rev ^ node.rev;
+            self.stack.push((&**node, e, rev));
+            now = if rev { &node.right } else { &node.left };
+            e = child_e;
+            rev = child_rev;
+        }
+    }
+}
+impl<'a, T: Monoid, E: Act<T>> Iterator for Iter<'a, T, E> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let (node, e, rev) = self.stack.pop()?;
+        let ret = e.act(&node.data);
+        let next = if rev { &node.left } else { &node.right };
+        self.push_spine(next, E::op(node.lazy.clone(), e), rev ^ node.rev);
+        Some(ret)
+    }
+}
+/// 中身を取り出しながら中順に返すやつ
+pub struct IntoIter<T: Monoid, E: Act<T>> {
+    stack: Vec<Box<Node<T, E>>>,
+}
+impl<T: Monoid, E: Act<T>> IntoIter<T, E> {
+    fn push_spine(&mut self, mut now: RBST<T, E>) {
+        while let Some(mut node) = now.0 {
+            node.push();
+            now = replace(&mut node.left, RBST::none());
+            self.stack.push(node);
+        }
+    }
+}
+impl<T: Monoid, E: Act<T>> Iterator for IntoIter<T, E> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let mut node = self.stack.pop()?;
+        self.push_spine(replace(&mut node.right, RBST::none()));
+        Some(node.data)
+    }
+}
+impl<T: Monoid, E: Act<T>> IntoIterator for RBST<T, E> {
+    type Item = T;
+    type IntoIter = IntoIter<T, E>;
+    fn into_iter(self) -> IntoIter<T, E> {
+        let mut ret = IntoIter { stack: Vec::new() };
+        ret.push_spine(self);
+        ret
+    }
+}
+impl<'a, T: Monoid, E: Act<T>> IntoIterator for &'a RBST<T, E> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, E>;
+    fn into_iter(self) -> Iter<'a, T, E> {
+        self.iter()
+    }
+}
+impl<T: Monoid, E: Act<T>> FromIterator<T> for RBST<T, E> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        //! O(n)で釣り合った木を作ります。
+        let data: Vec<T> = iter.into_iter().collect();
+        Self::build(&data, 0..data.len())
+    }
+}
+impl<T: Monoid, E: Act<T>> Extend<T> for RBST<T, E> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        //! 末尾に追加します。O(k + log n)
+        let sel = replace(self, Self::none());
+        *self = Self::merge(sel, iter.into_iter().collect());
+    }
+}
+impl<T: Monoid, E: Act<T>> fmt::Debug for RBST<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use algebra::*;
+    use algebra_struct::{AddMonoid, Ends};
+    use xorshift::Xorshift256;
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+    #[test]
+    fn access_test() {
+        let mut rng = Xorshift256::new();
+        let mut naive: Vec<i64> = (0..30).collect();
+        let mut t: RBST<AddMonoid> = naive.iter().map(|&x| AddMonoid(x)).collect();
+        for _ in 0..100 {
+            let l = rng.gen() as usize % 31;
+            let r = l + rng.gen() as usize % (31 - l);
+            t.reverse(l..r);
+            naive[l..r].reverse();
+            let shared = &t;
+            for (i, &x) in naive.iter().enumerate() {
+                assert_eq!(*shared.access(i), AddMonoid(x));
+            }
+        }
+    }
+    #[test]
+    fn lazy_reverse_test() {
+        let mut rng = Xorshift256::new();
+        let mut naive: Vec<i64> = (0..10).map(|_| (rng.gen() % 100) as i64).collect();
+        let init: Vec<_> = naive.iter().map(|&x| Ends::leaf(x)).collect();
+        let mut t: RBST<Ends, i64> = RBST::build(&init, 0..init.len());
+        for _ in 0..3000 {
+            let n = naive.len();
+            let l = rng.gen() as usize % (n + 1);
+            let r = l + rng.gen() as usize % (n - l + 1);
+            match rng.gen() % 6 {
+                0 | 1 => {
+                    let x = (rng.gen() % 100) as i64;
+                    t.insert(l, Ends::leaf(x));
+                    naive.insert(l, x);
+                }
+                2 => {
+                    if l < n {
+                        assert_eq!(t.erase(l), Ends::leaf(naive.remove(l)));
+                    }
+                }
+                3 => {
+                    let e = (rng.gen() % 10) as i64;
+                    t.apply(l..r, e);
+                    for x in &mut naive[l..r] {
+                        *x += e;
+                    }
+                }
+                4 => {
+                    t.reverse(l..r);
+                    naive[l..r].reverse();
+                }
+                _ => {
+                    let got = t.query(l..r);
+                    let expected = naive[l..r]
+                        .iter()
+                        .fold(Ends::identity(), |acc, &x| Ends::op(acc, Ends::leaf(x)));
+                    assert_eq!(got, expected);
+                    if l < n {
+                        assert_eq!(t.get(l).sum, naive[l]);
+                    }
+                }
+            }
+            assert_eq!(t.size(), naive.len());
+        }
+    }
+    #[test]
+    fn iter_test() {
+        let mut rng = Xorshift256::new();
+        let mut naive: Vec<i64> = (0..50).collect();
+        let mut t: RBST<Ends, i64> = naive.iter().map(|&x| Ends::leaf(x)).collect();
+        for _ in 0..300 {
+            let n = naive.len();
+            let l = rng.gen() as usize % (n + 1);
+            let r = l + rng.gen() as usize % (n - l + 1);
+            if rng.gen() & 1 == 0 {
+                let e = (rng.gen() % 10) as i64;
+                t.apply(l..r, e);
+                for x in &mut naive[l..r] {
+                    *x += e;
+                }
+            } else {
+                t.reverse(l..r);
+                naive[l..r].reverse();
+            }
+            let got: Vec<_> = t.iter().map(|x| x.sum).collect();
+            assert_eq!(got, naive);
+        }
+        t.extend((100..110).map(Ends::leaf));
+        naive.extend(100..110);
+        let got: Vec<_> = (&t).into_iter().map(|x| x.sum).collect();
+        assert_eq!(got, naive);
+        let got: Vec<_> = t.into_iter().map(|x| x.sum).collect();
+        assert_eq!(got, naive);
+        let small: RBST<AddMonoid> = (1..4).map(AddMonoid).collect();
+        assert_eq!(
+            format!("{:?}", small),
+            format!("{:?}", vec![AddMonoid(1), AddMonoid(2), AddMonoid(3)])
+        );
+    }
 }
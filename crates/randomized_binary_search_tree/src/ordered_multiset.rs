@@ -0,0 +1,245 @@
+use crate::{Node, RBST};
+use std::mem::replace;
+use std::ops::{Bound, RangeBounds};
+
+/// 同じ値を複数持てる順序付き集合
+/// 中身は昇順に並べたRBSTです。積は持たないので、モノイドにする必要はありません。
+pub struct OrderedMultiset<T: Ord>(RBST<T>);
+impl<T: Ord> OrderedMultiset<T> {
+    pub fn new() -> Self {
+        Self(RBST::none())
+    }
+    pub fn len(&self) -> usize {
+        self.0.size()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn bisect<F: Fn(&T) -> bool>(&self, f: F) -> usize {
+        // f(x)がtrueになる最初の位置。fは単調です。
+        let mut now = &self.0;
+        let mut ret = 0;
+        while let Some(ref node) = now.0 {
+            if f(&node.data) {
+                now = &node.left;
+            } else {
+                ret += node.left.size() + 1;
+                now = &node.right;
+            }
+        }
+        ret
+    }
+    pub fn lower_bound(&self, x: &T) -> usize {
+        //! x以上の最初の位置
+        self.bisect(|y| y >= x)
+    }
+    pub fn upper_bound(&self, x: &T) -> usize {
+        //! xより大きい最初の位置
+        self.bisect(|y| y > x)
+    }
+    pub fn rank(&self, x: &T) -> usize {
+        //! xより小さい要素の個数
+        self.lower_bound(x)
+    }
+    pub fn count(&self, x: &T) -> usize {
+        self.upper_bound(x) - self.lower_bound(x)
+    }
+    pub fn contains(&self, x: &T) -> bool {
+        self.kth(self.lower_bound(x)) == Some(x)
+    }
+    pub fn kth(&self, mut k: usize) -> Option<&T> {
+        //! 小さい方からk番目 (0-indexed)
+        let mut now = &self.0;
+        while let Some(ref node) = now.0 {
+            let rank = node.left.size();
+            if k < rank {
+                now = &node.left;
+            } else if k == rank {
+                return Some(&node.data);
+            } else {
+                k -= rank + 1;
+                now = &node.right;
+            }
+        }
+        None
+    }
+    pub fn insert(&mut self, x: T) {
+        let k = self.upper_bound(&x);
+        let (l, r) = replace(&mut self.0, RBST::none()).split_plain(k);
+        self.0 = RBST::merge_plain(RBST::merge_plain(l, RBST::leaf(x)), r);
+    }
+    pub fn remove_one(&mut self, x: &T) -> bool {
+        //! xを1個だけ消します。なければfalse
+        let k = self.lower_bound(x);
+        if self.kth(k) != Some(x) {
+            return false;
+        }
+        let (l, r) = replace(&mut self.0, RBST::none()).split_plain(k);
+        self.0 = RBST::merge_plain(l, r.split_plain(1).1);
+        true
+    }
+    pub fn split_off(&mut self, x: &T) -> Self {
+        //! x以上の要素を切り離して返します。O(log n)
+        let k = self.lower_bound(x);
+        let (l, r) = replace(&mut self.0, RBST::none()).split_plain(k);
+        self.0 = l;
+        Self(r)
+    }
+    pub fn merge(x: Self, y: Self) -> Self {
+        //! xの要素が全部yの要素以下である必要があります。O(log n)
+        if let (Some(a), Some(b)) = (x.kth(x.len().wrapping_sub(1)), y.kth(0)) {
+            assert!(a <= b);
+        }
+        Self(RBST::merge_plain(x.0, y.0))
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.iter_from(0, self.len())
+    }
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        //! 値がrangeに入るものを昇順に
+        let l = match range.start_bound() {
+            Bound::Included(x) => self.lower_bound(x),
+            Bound::Excluded(x) => self.upper_bound(x),
+            Bound::Unbounded => 0,
+        };
+        let r = match range.end_bound() {
+            Bound::Included(x) => self.upper_bound(x),
+            Bound::Excluded(x) => self.lower_bound(x),
+            Bound::Unbounded => self.len(),
+        };
+        self.iter_from(l, r.max(l))
+    }
+    fn iter_from(&self, l: usize, r: usize) -> Iter<'_, T> {
+        // l番目までの経路のうち、左に降りたところを積みます。
+        let rest = r - l;
+        let mut l = l;
+        let mut stack = Vec::new();
+        let mut now = &self.0;
+        while let Some(ref node) = now.0 {
+            let rank = node.left.size();
+            if l <= rank {
+                stack.push(&**node);
+                if l == rank {
+                    break;
+                }
+                now = &node.left;
+            } else {
+                l -= rank + 1;
+                now = &node.right;
+            }
+        }
+        Iter { stack, rest }
+    }
+}
+impl<T: Ord> Default for OrderedMultiset<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T, ()>>,
+    rest: usize,
+}
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        if self.rest == 0 {
+            return None;
+        }
+        self.rest -= 1;
+        let node = self.stack.pop()?;
+        let mut now = &node.right;
+        while let Some(ref x) = now.0 {
+            self.stack.push(&**x);
+            now = &x.left;
+        }
+        Some(&node.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use xorshift::Xorshift256;
+    #[test]
+    fn ordered_multiset_test() {
+        let mut rng = Xorshift256::new();
+        let mut set = OrderedMultiset::new();
+        let mut naive = BTreeMap::new();
+        for _ in 0..3000 {
+            let x = (rng.gen() % 50) as i64;
+            match rng.gen() % 5 {
+                0 | 1 => {
+                    set.insert(x);
+                    *naive.entry(x).or_insert(0) += 1;
+                }
+                2 => {
+                    let expected = match naive.get_mut(&x) {
+                        Some(c) => {
+                            *c -= 1;
+                            if *c == 0 {
+                                naive.remove(&x);
+                            }
+                            true
+                        }
+                        None => false,
+                    };
+                    assert_eq!(set.remove_one(&x), expected);
+                }
+                3 => {
+                    let y = (rng.gen() % 50) as i64;
+                    let (l, r) = (x.min(y), x.max(y));
+                    let got: Vec<_> = set.range(l..r).copied().collect();
+                    let expected: Vec<_> = naive
+                        .range(l..r)
+                        .flat_map(|(&k, &c)| (0..c).map(move |_| k))
+                        .collect();
+                    assert_eq!(got, expected);
+                    let got: Vec<_> = set.range(l..=r).copied().collect();
+                    let expected: Vec<_> = naive
+                        .range(l..=r)
+                        .flat_map(|(&k, &c)| (0..c).map(move |_| k))
+                        .collect();
+                    assert_eq!(got, expected);
+                }
+                _ => {
+                    let less: usize = naive.range(..x).map(|(_, &c)| c).sum();
+                    let c = naive.get(&x).copied().unwrap_or(0);
+                    assert_eq!(set.rank(&x), less);
+                    assert_eq!(set.lower_bound(&x), less);
+                    assert_eq!(set.upper_bound(&x), less + c);
+                    assert_eq!(set.count(&x), c);
+                    assert_eq!(set.contains(&x), c > 0);
+                    if c > 0 {
+                        assert_eq!(set.kth(less), Some(&x));
+                    }
+                }
+            }
+            assert_eq!(set.len(), naive.values().sum::<usize>());
+        }
+        let all: Vec<_> = set.iter().copied().collect();
+        for (i, x) in all.iter().enumerate() {
+            assert_eq!(set.kth(i), Some(x));
+        }
+        assert_eq!(set.kth(all.len()), None);
+        let mut sorted = all.clone();
+        sorted.sort();
+        assert_eq!(all, sorted);
+        // 切って戻す
+        let hi = set.split_off(&25);
+        assert!(set.iter().all(|&x| x < 25));
+        assert!(hi.iter().all(|&x| x >= 25));
+        let set = OrderedMultiset::merge(set, hi);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), all);
+        // Ordだけあれば使えます。
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Key(u32);
+        let mut keys = OrderedMultiset::new();
+        keys.insert(Key(2));
+        keys.insert(Key(1));
+        assert!(keys.contains(&Key(1)));
+        assert_eq!(keys.rank(&Key(2)), 1);
+    }
+}
@@ -0,0 +1,426 @@
+use crate::rand;
+use algebra::Monoid;
+use my_range::MyRange;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// 永続RBST
+/// 更新は全部経路をコピーした新しい木を返します。cloneはO(1)です。
+/// 版ごとに、その版になるまでに作ったノードの数を数えています。
+/// copy_pasteとrepeatはこれが長さの2倍を超えたら、古い版との共有を切って作り直します。
+#[derive(Clone)]
+pub struct PersistentRBST<T: Monoid> {
+    root: Tree<T>,
+    // 古い版と共有している分も数えます。
+    made: usize,
+}
+impl<T: Monoid> PersistentRBST<T> {
+    pub fn none() -> Self {
+        Self {
+            root: Tree(None),
+            made: 0,
+        }
+    }
+    pub fn build(data: &[T]) -> Self {
+        //! O(n)
+        let mut made = 0;
+        let root = Tree::build(data, &mut made);
+        Self { root, made }
+    }
+    pub fn size(&self) -> usize {
+        self.root.size()
+    }
+    pub fn height(&self) -> usize {
+        //! O(1)
+        self.root.height()
+    }
+    pub fn allocated(&self) -> usize {
+        //! この版になるまでに作ったノードの数。O(1)
+        //! 古い版を全部残しているときに使っているメモリの目安です。
+        self.made
+    }
+    pub fn sum(&self) -> T {
+        self.root.sum()
+    }
+    pub fn access(&self, mut k: usize) -> &T {
+        assert!(k < self.size());
+        let mut now = &self.root;
+        loop {
+            let node = now.0.as_ref().unwrap();
+            let rank = node.left.size();
+            if k < rank {
+                now = &node.left;
+            } else if k == rank {
+                return &node.data;
+            } else {
+                k -= rank + 1;
+                now = &node.right;
+            }
+        }
+    }
+    pub fn merge(x: &Self, y: &Self) -> Self {
+        //! O(log n)
+        let mut made = x.made + y.made;
+        let root = Tree::merge(&x.root, &y.root, &mut made);
+        Self { root, made }
+    }
+    pub fn split(&self, k: usize) -> (Self, Self) {
+        //! [0, k)と[k, n)に分けます。O(log n)
+        let mut made = self.made;
+        let (a, b) = self.root.split(k, &mut made);
+        (Self { root: a, made }, Self { root: b, made })
+    }
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let mut made = self.made;
+        let root = self.root.slice(range, &mut made);
+        Self { root, made }
+    }
+    pub fn insert(&self, k: usize, val: T) -> Self {
+        let mut made = self.made;
+        let (l, r) = self.root.split(k, &mut made);
+        let x = Tree::new_node(Tree(None), val, Tree(None), &mut made);
+        let root = Tree::merge(&Tree::merge(&l, &x, &mut made), &r, &mut made);
+        Self { root, made }
+    }
+    pub fn erase(&self, k: usize) -> Self {
+        assert!(k < self.size());
+        let mut made = self.made;
+        let (l, r) = self.root.split(k, &mut made);
+        let r = r.split(1, &mut made).1;
+        let root = Tree::merge(&l, &r, &mut made);
+        Self { root, made }
+    }
+    pub fn set(&self, k: usize, val: T) -> Self {
+        assert!(k < self.size());
+        let mut made = self.made;
+        let root = self.root.set(k, val, &mut made);
+        Self { root, made }
+    }
+    pub fn copy_paste(&self, src: Range<usize>, dst: usize) -> Self {
+        //! srcの部分のコピーをdstの位置に挿入します。O(log n)
+        //! 作り直すときだけO(n)です。
+        let mut made = self.made;
+        let part = self.root.slice(src, &mut made);
+        let (l, r) = self.root.split(dst, &mut made);
+        let root = Tree::merge(&Tree::merge(&l, &part, &mut made), &r, &mut made);
+        Self { root, made }.rebuild_if_bloated()
+    }
+    pub fn repeat(&self, k: usize) -> Self {
+        //! k回並べたもの。O(log n log k)
+        //! 作り直すときだけO(n k)です。
+        let mut made = self.made;
+        let mut ret = Tree(None);
+        let mut x = self.root.clone();
+        let mut k = k;
+        while k > 0 {
+            if k & 1 == 1 {
+                ret = Tree::merge(&ret, &x, &mut made);
+            }
+            x = Tree::merge(&x, &x, &mut made);
+            k >>= 1;
+        }
+        Self { root: ret, made }.rebuild_if_bloated()
+    }
+    pub fn query(&self, range: Range<usize>) -> T {
+        //! O(log n)、ノードは作りません。
+        assert!((0..self.size()).contains_range(&range));
+        let (mut l, mut r) = (range.start, range.end);
+        // rangeが左右の子に分かれるノードまで降ります。
+        let mut now = &self.root;
+        let node = loop {
+            let node = match now.0 {
+                Some(ref node) if l < r => node,
+                _ => return T::identity(),
+            };
+            if l == 0 && r == node.size {
+                return node.sum.clone();
+            }
+            let rank = node.left.size();
+            if r <= rank {
+                now = &node.left;
+            } else if rank < l {
+                l -= rank + 1;
+                r -= rank + 1;
+                now = &node.right;
+            } else {
+                break node;
+            }
+        };
+        // 左の子は[l, ..)を後ろから、右の子は[.., r)を前から足します。
+        let mut left = T::identity();
+        let mut now = &node.left;
+        while let Some(ref x) = now.0 {
+            let rank = x.left.size();
+            if l == 0 {
+                left = T::op(x.sum.clone(), left);
+                break;
+            } else if l <= rank {
+                left = T::op(T::op(x.data.clone(), x.right.sum()), left);
+                now = &x.left;
+            } else {
+                l -= rank + 1;
+                now = &x.right;
+            }
+        }
+        let mut right = T::identity();
+        let mut r = r - node.left.size() - 1;
+        let mut now = &node.right;
+        while let Some(ref x) = now.0 {
+            let rank = x.left.size();
+            if r == x.size {
+                right = T::op(right, x.sum.clone());
+                break;
+            } else if rank < r {
+                right = T::op(T::op(right, x.left.sum()), x.data.clone());
+                r -= rank + 1;
+                now = &x.right;
+            } else {
+                now = &x.left;
+            }
+        }
+        T::op(T::op(left, node.data.clone()), right)
+    }
+    pub fn to_vec(&self) -> Vec<T> {
+        // 高さが伸びていてもいいように、スタックで中順に辿ります。
+        let mut ret = Vec::with_capacity(self.size());
+        let mut stack = Vec::new();
+        let mut now = &self.root;
+        loop {
+            while let Some(ref node) = now.0 {
+                stack.push(&**node);
+                now = &node.left;
+            }
+            match stack.pop() {
+                Some(node) => {
+                    ret.push(node.data.clone());
+                    now = &node.right;
+                }
+                None => return ret,
+            }
+        }
+    }
+    pub fn rebuild(&self) -> Self {
+        //! 古い版との共有を切って釣り合った木に作り直します。O(n)
+        //! 古い版を捨てればノード数がsize()まで減ります。
+        Self::build(&self.to_vec())
+    }
+    fn rebuild_if_bloated(self) -> Self {
+        // 作り直しのO(n)は、それまでに作ったノードで均せます。
+        if self.made > 2 * self.size() {
+            self.rebuild()
+        } else {
+            self
+        }
+    }
+}
+
+// 作ったノードの数をmadeに足していきます。
+#[derive(Clone)]
+struct Tree<T: Monoid>(Option<Rc<Node<T>>>);
+impl<T: Monoid> Tree<T> {
+    fn new_node(left: Self, data: T, right: Self, made: &mut usize) -> Self {
+        *made += 1;
+        let size = left.size() + right.size() + 1;
+        let height = left.height().max(right.height()) + 1;
+        let sum = T::op(T::op(left.sum(), data.clone()), right.sum());
+        Self(Some(Rc::new(Node {
+            left,
+            right,
+            size,
+            height,
+            data,
+            sum,
+        })))
+    }
+    fn build(data: &[T], made: &mut usize) -> Self {
+        if data.is_empty() {
+            return Self(None);
+        }
+        let mid = data.len() / 2;
+        let left = Self::build(&data[..mid], made);
+        let right = Self::build(&data[mid + 1..], made);
+        Self::new_node(left, data[mid].clone(), right, made)
+    }
+    fn size(&self) -> usize {
+        match self.0 {
+            Some(ref x) => x.size,
+            None => 0,
+        }
+    }
+    fn height(&self) -> usize {
+        match self.0 {
+            Some(ref x) => x.height,
+            None => 0,
+        }
+    }
+    fn sum(&self) -> T {
+        match self.0 {
+            Some(ref x) => x.sum.clone(),
+            None => T::identity(),
+        }
+    }
+    fn merge(x: &Self, y: &Self, made: &mut usize) -> Self {
+        match (&x.0, &y.0) {
+            (None, _) => y.clone(),
+            (_, None) => x.clone(),
+            (Some(a), Some(b)) => {
+                if rand() % ((a.size + b.size) as u64) < a.size as u64 {
+                    let right = Self::merge(&a.right, y, made);
+                    Self::new_node(a.left.clone(), a.data.clone(), right, made)
+                } else {
+                    let left = Self::merge(x, &b.left, made);
+                    Self::new_node(left, b.data.clone(), b.right.clone(), made)
+                }
+            }
+        }
+    }
+    fn split(&self, k: usize, made: &mut usize) -> (Self, Self) {
+        match self.0 {
+            None => (Self(None), Self(None)),
+            Some(ref node) => {
+                let rank = node.left.size();
+                if k <= rank {
+                    let (a, b) = node.left.split(k, made);
+                    (
+                        a,
+                        Self::new_node(b, node.data.clone(), node.right.clone(), made),
+                    )
+                } else {
+                    let (a, b) = node.right.split(k - rank - 1, made);
+                    (
+                        Self::new_node(node.left.clone(), node.data.clone(), a, made),
+                        b,
+                    )
+                }
+            }
+        }
+    }
+    fn slice(&self, range: Range<usize>, made: &mut usize) -> Self {
+        assert!((0..self.size()).contains_range(&range));
+        self.split(range.end, made).0.split(range.start, made).1
+    }
+    fn set(&self, k: usize, val: T, made: &mut usize) -> Self {
+        let node = self.0.as_ref().unwrap();
+        let rank = node.left.size();
+        if k < rank {
+            let left = node.left.set(k, val, made);
+            Self::new_node(left, node.data.clone(), node.right.clone(), made)
+        } else if k == rank {
+            Self::new_node(node.left.clone(), val, node.right.clone(), made)
+        } else {
+            let right = node.right.set(k - rank - 1, val, made);
+            Self::new_node(node.left.clone(), node.data.clone(), right, made)
+        }
+    }
+}
+struct Node<T: Monoid> {
+    left: Tree<T>,
+    right: Tree<T>,
+    size: usize,
+    height: usize,
+    data: T,
+    sum: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra_struct::AddMonoid;
+    use xorshift::Xorshift256;
+    #[test]
+    fn persistent_rbst_test() {
+        let mut rng = Xorshift256::new();
+        let init: Vec<i64> = (0..20).map(|_| (rng.gen() % 100) as i64).collect();
+        let t = PersistentRBST::build(&init.iter().map(|&x| AddMonoid(x)).collect::<Vec<_>>());
+        let mut versions = vec![(t, init)];
+        for _ in 0..1000 {
+            let (t, naive) = versions[rng.gen() as usize % versions.len()].clone();
+            let n = naive.len();
+            let l = rng.gen() as usize % (n + 1);
+            let r = l + rng.gen() as usize % (n - l + 1);
+            let (t, naive) = match rng.gen() % 6 {
+                0 => {
+                    let x = (rng.gen() % 100) as i64;
+                    let mut v = naive.clone();
+                    v.insert(l, x);
+                    (t.insert(l, AddMonoid(x)), v)
+                }
+                1 if l < n => {
+                    let mut v = naive.clone();
+                    v.remove(l);
+                    (t.erase(l), v)
+                }
+                2 if l < n => {
+                    let x = (rng.gen() % 100) as i64;
+                    let mut v = naive.clone();
+                    v[l] = x;
+                    (t.set(l, AddMonoid(x)), v)
+                }
+                3 if n < 1000 => {
+                    let dst = rng.gen() as usize % (n + 1);
+                    let mut v = naive[..dst].to_vec();
+                    v.extend_from_slice(&naive[l..r]);
+                    v.extend_from_slice(&naive[dst..]);
+                    (t.copy_paste(l..r, dst), v)
+                }
+                4 => (t.slice(l..r), naive[l..r].to_vec()),
+                _ => {
+                    let sum: i64 = naive[l..r].iter().sum();
+                    assert_eq!(t.query(l..r), AddMonoid(sum));
+                    if l < n {
+                        assert_eq!(*t.access(l), AddMonoid(naive[l]));
+                    }
+                    continue;
+                }
+            };
+            assert_eq!(t.size(), naive.len());
+            versions.push((t, naive));
+        }
+        for (t, naive) in &versions {
+            let v: Vec<_> = t.to_vec().into_iter().map(|x| x.0).collect();
+            assert_eq!(&v, naive);
+        }
+    }
+    #[test]
+    fn repeat_and_rebuild_test() {
+        let s: Vec<_> = b"abc".iter().map(|&c| AddMonoid(c as i64)).collect();
+        let t = PersistentRBST::build(&s);
+        let big = t.repeat(1_000_000_000);
+        assert_eq!(big.size(), 3_000_000_000);
+        assert_eq!(*big.access(2_999_999_999), AddMonoid(b'c' as i64));
+        assert_eq!(
+            big.query(1..3_000_000_000),
+            AddMonoid(294 * 1_000_000_000 - b'a' as i64)
+        );
+        let mut u = t.repeat(100);
+        let mut naive: Vec<_> = (0..100).flat_map(|_| s.iter().copied()).collect();
+        for i in 0..100 {
+            u = u.copy_paste(i..i + 50, 2 * i);
+            let part = naive[i..i + 50].to_vec();
+            naive.splice(2 * i..2 * i, part);
+        }
+        assert_eq!(u.to_vec(), naive);
+        let v = u.rebuild();
+        assert_eq!(v.to_vec(), naive);
+        // 作り直すと完全に釣り合います。
+        let mut log = 0;
+        while (1 << log) <= v.size() {
+            log += 1;
+        }
+        assert_eq!(v.height(), log);
+        assert!(v.height() <= u.height());
+        assert_eq!(v.allocated(), v.size());
+        // 1個ずつコピーするとノードばかり増えるので、途中で作り直されます。
+        let mut w = t.clone();
+        let mut naive = s.clone();
+        for i in 0..1000 {
+            let k = i * 7 % w.size();
+            let dst = i % (w.size() + 1);
+            w = w.copy_paste(k..k + 1, dst);
+            let x = naive[k];
+            naive.insert(dst, x);
+            assert!(w.allocated() <= 2 * w.size());
+        }
+        assert_eq!(w.to_vec(), naive);
+    }
+}
@@ -1,52 +1,296 @@
+use algebra::*;
+use segment_tree::SegmentTree;
+use static_modint::{Mod, Modint};
 use std::ops::Range;
+use xorshift::Xorshift256;
+
+const MOD: u64 = (1 << 61) - 1;
+fn mul(x: u64, y: u64) -> u64 {
+    let t: u128 = x as u128 * y as u128;
+    let t: u64 = ((t >> 61) + (t & MOD as u128)) as u64;
+    if t < MOD {
+        t
+    } else {
+        t - MOD
+    }
+}
+fn add(x: u64, y: u64) -> u64 {
+    let t = x + y;
+    if t < MOD {
+        t
+    } else {
+        t - MOD
+    }
+}
+fn sub(x: u64, y: u64) -> u64 {
+    add(x, MOD - y)
+}
+fn pow(mut x: u64, mut e: usize) -> u64 {
+    let mut ret = 1;
+    while e > 0 {
+        if e & 1 == 1 {
+            ret = mul(ret, x);
+        }
+        x = mul(x, x);
+        e >>= 1;
+    }
+    ret
+}
+fn pow_table(base: u64, n: usize) -> Vec<u64> {
+    let mut pow = vec![1; n + 1];
+    for i in 0..n {
+        pow[i + 1] = mul(pow[i], base);
+    }
+    pow
+}
+thread_local! {
+    static BASE: [u64; 2] = {
+        let mut rng = Xorshift256::new_with_date();
+        for _ in 0..16 {
+            rng.gen();
+        }
+        [rng.gen() % (MOD - 256) + 256, rng.gen() % (MOD - 256) + 256]
+    };
+}
+pub fn random_base() -> u64 {
+    //! 実行ごとにランダムに決まります。同じスレッド内では同じ値です。
+    BASE.with(|x| x[0])
+}
+
 pub struct RollingHash {
     hash: Vec<u64>,
     pow: Vec<u64>,
+    base: u64,
 }
 
 impl RollingHash {
-    const MOD: u64 = (1 << 61) - 1;
-    pub fn new(s: &[u8]) -> Self {
+    pub fn new<T: Into<u64> + Copy>(s: &[T]) -> Self {
+        Self::new_with_base(s, random_base())
+    }
+    pub fn new_with_base<T: Into<u64> + Copy>(s: &[T], base: u64) -> Self {
         let n = s.len();
         let mut hash = vec![0; n + 1];
-        let mut pow = vec![1; n + 1];
-        let base = 11;
         for i in 0..n {
-            hash[i + 1] = Self::mul(hash[i], base) + s[i] as u64;
-            pow[i + 1] = Self::mul(pow[i], base);
-            if hash[i + 1] >= Self::MOD {
-                hash[i + 1] -= Self::MOD;
-            }
+            hash[i + 1] = add(mul(hash[i], base), s[i].into() % MOD);
         }
-        Self { hash, pow }
+        Self {
+            hash,
+            pow: pow_table(base, n),
+            base,
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.hash.len() - 1
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn base(&self) -> u64 {
+        self.base
     }
     pub fn hash(&self, range: Range<usize>) -> u64 {
         let Range { start, end } = range;
-        let ret = Self::MOD + self.hash[end] - Self::mul(self.hash[start], self.pow[end - start]);
-        if ret < Self::MOD {
-            ret
+        sub(self.hash[end], mul(self.hash[start], self.pow[end - start]))
+    }
+    pub fn concat(&self, h1: u64, h2: u64, len2: usize) -> u64 {
+        //! hash(s + t) = concat(hash(s), hash(t), |t|)
+        let p = if len2 < self.pow.len() {
+            self.pow[len2]
         } else {
-            ret - Self::MOD
+            pow(self.base, len2)
+        };
+        add(mul(h1, p), h2)
+    }
+    pub fn lcp(&self, i: usize, other: &Self, j: usize) -> usize {
+        //! self[i..]とother[j..]の最長共通接頭辞の長さ。O(log n)
+        assert_eq!(self.base, other.base);
+        let (mut ok, mut ng) = (0, (self.len() - i).min(other.len() - j) + 1);
+        while ng - ok > 1 {
+            let mid = (ok + ng) / 2;
+            if self.hash(i..i + mid) == other.hash(j..j + mid) {
+                ok = mid;
+            } else {
+                ng = mid;
+            }
         }
+        ok
     }
-    fn mul(x: u64, y: u64) -> u64 {
-        let t: u128 = x as u128 * y as u128;
-        let t: u64 = ((t >> 61) + (t & Self::MOD as u128)) as u64;
-        if t < Self::MOD {
-            t
-        } else {
-            t - Self::MOD
+}
+
+/// 好きなmodで使うやつ。違うmodのものを並べればmulti-modになります。
+pub struct ModRollingHash<M: Mod> {
+    hash: Vec<Modint<M>>,
+    pow: Vec<Modint<M>>,
+}
+impl<M: Mod> ModRollingHash<M> {
+    pub fn new<T: Into<u64> + Copy>(s: &[T]) -> Self {
+        Self::new_with_base(s, random_base() % (M::M - 2) + 2)
+    }
+    pub fn new_with_base<T: Into<u64> + Copy>(s: &[T], base: u64) -> Self {
+        let n = s.len();
+        let base = Modint::new(base);
+        let mut hash = vec![Modint::new(0); n + 1];
+        let mut pow = vec![Modint::new(1); n + 1];
+        for i in 0..n {
+            hash[i + 1] = hash[i] * base + s[i].into();
+            pow[i + 1] = pow[i] * base;
         }
+        Self { hash, pow }
+    }
+    pub fn hash(&self, range: Range<usize>) -> Modint<M> {
+        let Range { start, end } = range;
+        self.hash[end] - self.hash[start] * self.pow[end - start]
+    }
+}
+
+/// hashと base^len の組です。
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HashValue {
+    pub hash: u64,
+    pub pow: u64,
+}
+impl HashValue {
+    pub fn new(x: u64, base: u64) -> Self {
+        Self {
+            hash: x % MOD,
+            pow: base,
+        }
+    }
+}
+impl Magma for HashValue {
+    fn op<L: Into<Self>, R: Into<Self>>(lhs: L, rhs: R) -> Self {
+        let (lhs, rhs) = (lhs.into(), rhs.into());
+        Self {
+            hash: add(mul(lhs.hash, rhs.pow), rhs.hash),
+            pow: mul(lhs.pow, rhs.pow),
+        }
+    }
+}
+impl Semigroup for HashValue {}
+impl Identity for HashValue {
+    fn identity() -> Self {
+        Self { hash: 0, pow: 1 }
+    }
+}
+
+/// 一点更新できるやつ
+pub struct UpdatableRollingHash {
+    seg: SegmentTree<HashValue>,
+    base: u64,
+}
+impl UpdatableRollingHash {
+    pub fn new<T: Into<u64> + Copy>(s: &[T]) -> Self {
+        Self::new_with_base(s, random_base())
+    }
+    pub fn new_with_base<T: Into<u64> + Copy>(s: &[T], base: u64) -> Self {
+        let init: Vec<_> = s.iter().map(|&x| HashValue::new(x.into(), base)).collect();
+        Self {
+            seg: SegmentTree::new_with_init(&init),
+            base,
+        }
+    }
+    pub fn set<T: Into<u64>>(&mut self, idx: usize, x: T) {
+        self.seg.set(idx, HashValue::new(x.into(), self.base));
+    }
+    pub fn hash(&self, range: Range<usize>) -> u64 {
+        self.seg.query(range).hash
+    }
+}
+
+/// グリッドの長方形のhash
+pub struct RollingHash2D {
+    hash: Vec<Vec<u64>>,
+    pow_row: Vec<u64>,
+    pow_col: Vec<u64>,
+}
+impl RollingHash2D {
+    pub fn new<T: Into<u64> + Copy>(grid: &[Vec<T>]) -> Self {
+        Self::new_with_base(grid, random_base(), BASE.with(|x| x[1]))
+    }
+    pub fn new_with_base<T: Into<u64> + Copy>(
+        grid: &[Vec<T>],
+        base_row: u64,
+        base_col: u64,
+    ) -> Self {
+        let h = grid.len();
+        let w = if h > 0 { grid[0].len() } else { 0 };
+        let mut hash = vec![vec![0; w + 1]; h + 1];
+        for i in 0..h {
+            for j in 0..w {
+                let x = add(mul(hash[i + 1][j], base_col), grid[i][j].into() % MOD);
+                hash[i + 1][j + 1] = add(
+                    x,
+                    mul(sub(hash[i][j + 1], mul(hash[i][j], base_col)), base_row),
+                );
+            }
+        }
+        Self {
+            hash,
+            pow_row: pow_table(base_row, h),
+            pow_col: pow_table(base_col, w),
+        }
+    }
+    pub fn hash(&self, rows: Range<usize>, cols: Range<usize>) -> u64 {
+        let (pr, pc) = (
+            self.pow_row[rows.end - rows.start],
+            self.pow_col[cols.end - cols.start],
+        );
+        let a = self.hash[rows.end][cols.end];
+        let b = mul(self.hash[rows.start][cols.end], pr);
+        let c = mul(self.hash[rows.end][cols.start], pc);
+        let d = mul(mul(self.hash[rows.start][cols.start], pr), pc);
+        add(sub(sub(a, b), c), d)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use static_modint::{P1000000007, P998244353};
     #[test]
     fn rollinghash_test() {
         let s = "abcabcd";
         let h = RollingHash::new(s.as_bytes());
         assert_eq!(h.hash(0..3), h.hash(3..6));
+        assert_ne!(h.hash(0..4), h.hash(3..7));
+        assert_eq!(h.concat(h.hash(0..2), h.hash(2..5), 3), h.hash(0..5));
+        let t = RollingHash::new("xxabcabd".as_bytes());
+        assert_eq!(h.lcp(0, &t, 2), 5);
+        assert_eq!(h.lcp(3, &t, 0), 0);
+        let v = RollingHash::new(&[1u32, 2, 3, 1, 2, 3]);
+        assert_eq!(v.hash(0..3), v.hash(3..6));
+    }
+    #[test]
+    fn multi_mod_test() {
+        let s = "abcabcd".as_bytes();
+        let h1 = ModRollingHash::<P1000000007>::new(s);
+        let h2 = ModRollingHash::<P998244353>::new(s);
+        let pair = |l: usize, r: usize| (h1.hash(l..r), h2.hash(l..r));
+        assert_eq!(pair(0, 3), pair(3, 6));
+        assert_ne!(pair(0, 4), pair(3, 7));
+    }
+    #[test]
+    fn updatable_test() {
+        let mut s = b"abcabcd".to_vec();
+        let mut h = UpdatableRollingHash::new(&s);
+        let r = RollingHash::new(&s);
+        assert_eq!(h.hash(1..6), r.hash(1..6));
+        h.set(6, b'a');
+        s[6] = b'a';
+        let r = RollingHash::new(&s);
+        assert_eq!(h.hash(0..7), r.hash(0..7));
+        assert_eq!(h.hash(3..7), h.hash(0..4));
+    }
+    #[test]
+    fn grid_test() {
+        let grid: Vec<Vec<u8>> = ["abab", "cdcd", "abab"]
+            .iter()
+            .map(|s| s.bytes().collect())
+            .collect();
+        let h = RollingHash2D::new(&grid);
+        assert_eq!(h.hash(0..2, 0..2), h.hash(0..2, 2..4));
+        assert_eq!(h.hash(0..1, 0..4), h.hash(2..3, 0..4));
+        assert_ne!(h.hash(0..2, 0..2), h.hash(1..3, 0..2));
+        assert_ne!(h.hash(0..2, 0..2), h.hash(0..2, 1..3));
     }
 }
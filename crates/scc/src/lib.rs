@@ -0,0 +1,205 @@
+use graph::Graph;
+mod two_sat;
+pub use two_sat::*;
+
+const NONE: usize = !0;
+
+/// 強連結成分分解。成分の番号はトポロジカル順です。
+pub struct StronglyConnectedComponents {
+    id: Vec<usize>,
+    groups: Vec<Vec<usize>>,
+}
+impl StronglyConnectedComponents {
+    fn from_id(id: Vec<usize>, count: usize) -> Self {
+        let mut groups = vec![Vec::new(); count];
+        for (v, &c) in id.iter().enumerate() {
+            groups[c].push(v);
+        }
+        Self { id, groups }
+    }
+    pub fn new<W>(g: &Graph<W>) -> Self {
+        //! Tarjan。O(n + m)
+        let n = g.size();
+        let mut ord = vec![NONE; n];
+        let mut low = vec![0; n];
+        let mut id = vec![NONE; n];
+        let mut stack = Vec::new();
+        let mut call = Vec::new();
+        let (mut now, mut count) = (0, 0);
+        for s in 0..n {
+            if ord[s] != NONE {
+                continue;
+            }
+            ord[s] = now;
+            low[s] = now;
+            now += 1;
+            stack.push(s);
+            call.push((s, 0));
+            while let Some(&mut (v, ref mut i)) = call.last_mut() {
+                if *i < g[v].len() {
+                    let to = g[v][*i].to;
+                    *i += 1;
+                    if ord[to] == NONE {
+                        ord[to] = now;
+                        low[to] = now;
+                        now += 1;
+                        stack.push(to);
+                        call.push((to, 0));
+                    } else if id[to] == NONE {
+                        // まだスタックに乗っています。
+                        low[v] = low[v].min(ord[to]);
+                    }
+                    continue;
+                }
+                call.pop();
+                if let Some(&(p, _)) = call.last() {
+                    low[p] = low[p].min(low[v]);
+                }
+                if low[v] == ord[v] {
+                    loop {
+                        let x = stack.pop().unwrap();
+                        id[x] = count;
+                        if x == v {
+                            break;
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+        // Tarjanでは逆トポロジカル順に見つかります。
+        for x in id.iter_mut() {
+            *x = count - 1 - *x;
+        }
+        Self::from_id(id, count)
+    }
+    pub fn kosaraju<W: Copy>(g: &Graph<W>) -> Self {
+        //! O(n + m)
+        let n = g.size();
+        let mut visited = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        for s in 0..n {
+            if visited[s] {
+                continue;
+            }
+            visited[s] = true;
+            let mut call = vec![(s, 0)];
+            while let Some(&mut (v, ref mut i)) = call.last_mut() {
+                if *i < g[v].len() {
+                    let to = g[v][*i].to;
+                    *i += 1;
+                    if !visited[to] {
+                        visited[to] = true;
+                        call.push((to, 0));
+                    }
+                } else {
+                    order.push(v);
+                    call.pop();
+                }
+            }
+        }
+        let r = g.reversed();
+        let mut id = vec![NONE; n];
+        let mut count = 0;
+        for &s in order.iter().rev() {
+            if id[s] != NONE {
+                continue;
+            }
+            id[s] = count;
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for e in &r[v] {
+                    if id[e.to] == NONE {
+                        id[e.to] = count;
+                        stack.push(e.to);
+                    }
+                }
+            }
+            count += 1;
+        }
+        Self::from_id(id, count)
+    }
+    pub fn size(&self) -> usize {
+        //! 成分の個数
+        self.groups.len()
+    }
+    pub fn id(&self, v: usize) -> usize {
+        self.id[v]
+    }
+    pub fn ids(&self) -> &[usize] {
+        &self.id
+    }
+    pub fn groups(&self) -> &[Vec<usize>] {
+        //! トポロジカル順に並んだ各成分の頂点
+        &self.groups
+    }
+    pub fn condensation<W>(&self, g: &Graph<W>) -> Graph<()> {
+        //! 成分を1頂点に潰したDAG。多重辺はまとめます。
+        let mut edges = Vec::new();
+        for v in 0..g.size() {
+            for e in &g[v] {
+                let (a, b) = (self.id[v], self.id[e.to]);
+                if a != b {
+                    edges.push((a, b));
+                }
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        Graph::directed_unweighted(self.size(), &edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn scc_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..100 {
+            let n = 1 + rng.gen() as usize % 10;
+            let m = rng.gen() as usize % 20;
+            let edges: Vec<_> = (0..m)
+                .map(|_| (rng.gen() as usize % n, rng.gen() as usize % n))
+                .collect();
+            let g = Graph::directed_unweighted(n, &edges);
+            // 到達可能性
+            let mut reach = vec![vec![false; n]; n];
+            for (v, row) in reach.iter_mut().enumerate() {
+                row[v] = true;
+            }
+            for &(u, v) in &edges {
+                reach[u][v] = true;
+            }
+            for k in 0..n {
+                for i in 0..n {
+                    for j in 0..n {
+                        if reach[i][k] && reach[k][j] {
+                            reach[i][j] = true;
+                        }
+                    }
+                }
+            }
+            let tarjan = StronglyConnectedComponents::new(&g);
+            let kosaraju = StronglyConnectedComponents::kosaraju(&g);
+            for scc in [&tarjan, &kosaraju].iter() {
+                for (u, row) in reach.iter().enumerate() {
+                    for (v, &r) in row.iter().enumerate() {
+                        assert_eq!(scc.id(u) == scc.id(v), r && reach[v][u]);
+                    }
+                }
+                for &(u, v) in &edges {
+                    assert!(scc.id(u) <= scc.id(v));
+                }
+                let dag = scc.condensation(&g);
+                assert_eq!(dag.size(), scc.size());
+                for c in 0..dag.size() {
+                    assert!(dag[c].iter().all(|e| c < e.to));
+                }
+                let total: usize = scc.groups().iter().map(|x| x.len()).sum();
+                assert_eq!(total, n);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::StronglyConnectedComponents;
+use graph::Graph;
+
+pub struct TwoSat {
+    n: usize,
+    edges: Vec<(usize, usize)>,
+    answer: Vec<bool>,
+}
+impl TwoSat {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            edges: Vec::new(),
+            answer: vec![false; n],
+        }
+    }
+    pub fn add_clause(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        //! (x_i == f) || (x_j == g) を追加します。
+        assert!(i < self.n && j < self.n);
+        // 頂点2i + 1が x_i == true です。
+        self.edges
+            .push((2 * i + if f { 0 } else { 1 }, 2 * j + if g { 1 } else { 0 }));
+        self.edges
+            .push((2 * j + if g { 0 } else { 1 }, 2 * i + if f { 1 } else { 0 }));
+    }
+    pub fn satisfiable(&mut self) -> bool {
+        //! O(n + m)
+        let scc =
+            StronglyConnectedComponents::new(&Graph::directed_unweighted(2 * self.n, &self.edges));
+        for i in 0..self.n {
+            let (x, y) = (scc.id(2 * i), scc.id(2 * i + 1));
+            if x == y {
+                return false;
+            }
+            self.answer[i] = x < y;
+        }
+        true
+    }
+    pub fn answer(&self) -> &[bool] {
+        //! satisfiableがtrueを返した後に使ってください。
+        &self.answer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn two_sat_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..200 {
+            let n = 1 + rng.gen() as usize % 6;
+            let m = rng.gen() as usize % 15;
+            let clauses: Vec<_> = (0..m)
+                .map(|_| {
+                    (
+                        rng.gen() as usize % n,
+                        rng.gen() & 1 == 0,
+                        rng.gen() as usize % n,
+                        rng.gen() & 1 == 0,
+                    )
+                })
+                .collect();
+            let mut ts = TwoSat::new(n);
+            for &(i, f, j, g) in &clauses {
+                ts.add_clause(i, f, j, g);
+            }
+            let check = |x: &dyn Fn(usize) -> bool| {
+                clauses.iter().all(|&(i, f, j, g)| x(i) == f || x(j) == g)
+            };
+            let naive = (0..1usize << n).any(|b| check(&|i| b >> i & 1 == 1));
+            assert_eq!(ts.satisfiable(), naive);
+            if naive {
+                let answer = ts.answer();
+                assert!(check(&|i| answer[i]));
+            }
+        }
+    }
+}
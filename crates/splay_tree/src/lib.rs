@@ -1,12 +1,14 @@
-use algebra::Monoid;
+use algebra::{Act, Monoid};
 use my_range::MyRange;
+use std::fmt;
+use std::iter::FromIterator;
 use std::mem::{replace, swap};
 use std::ops::Range;
 
 // 列をあつかうやーつ
-#[derive(Debug)]
-pub struct SplayTree<T: Monoid>(Option<Box<Node<T>>>);
-impl<T: Monoid> SplayTree<T> {
+// Eは区間に作用させるもので、いらなければ()です。
+pub struct SplayTree<T: Monoid, E: Act<T> = ()>(Option<Box<Node<T, E>>>);
+impl<T: Monoid, E: Act<T>> SplayTree<T, E> {
     pub fn size(&self) -> usize {
         match self.0 {
             Some(ref x) => x.size,
@@ -14,6 +16,7 @@ impl<T: Monoid> SplayTree<T> {
         }
     }
     pub fn access(&mut self, k: usize) -> &T {
+        //! k番目をsplayして根に持ってくるので&mut selfです。償却O(log n)
         assert!(self.size() > k);
         self.splay(k);
         &self.as_ref().data
@@ -22,6 +25,7 @@ impl<T: Monoid> SplayTree<T> {
         match self {
             Self(None) => (Self::none(), Self::none()),
             _ => {
+                self.as_mut().push();
                 if k <= self.as_ref().left.size() {
                     // 左に行きます。
                     let x = replace(&mut self.as_mut().left, Self::none()).split(k);
@@ -73,22 +77,55 @@ impl<T: Monoid> SplayTree<T> {
         *self = Self::merge(Self::merge(a, b), c);
         ret
     }
+    pub fn apply(&mut self, range: Range<usize>, e: E) {
+        //! range内の全部にeを作用させます。
+        assert!((0..self.size()).contains_range(&range));
+        let sel = replace(self, Self::none());
+        let (a, b) = sel.split(range.start);
+        let (mut b, c) = b.split(range.end - range.start);
+        b.apply_all(&e);
+        *self = Self::merge(Self::merge(a, b), c);
+    }
+    pub fn reverse(&mut self, range: Range<usize>) {
+        assert!((0..self.size()).contains_range(&range));
+        let sel = replace(self, Self::none());
+        let (a, b) = sel.split(range.start);
+        let (mut b, c) = b.split(range.end - range.start);
+        b.toggle();
+        *self = Self::merge(Self::merge(a, b), c);
+    }
     pub fn none() -> Self {
         Self(None)
     }
+    pub fn build(data: &[T], range: Range<usize>) -> Self {
+        if range.start == range.end {
+            return Self::none();
+        }
+        let mid = (range.start + range.end) / 2;
+        let mut ret = Node::new(data[mid].clone());
+        ret.left = Self::build(data, range.start..mid);
+        ret.right = Self::build(data, mid + 1..range.end);
+        ret.update();
+        Self(Some(Box::new(ret)))
+    }
+    pub fn iter(&self) -> Iter<'_, T, E> {
+        let mut ret = Iter { stack: Vec::new() };
+        ret.push_spine(self, E::identity(), false);
+        ret
+    }
     fn is_none(&self) -> bool {
         self.0.is_none()
     }
-    fn as_ref(&self) -> &Box<Node<T>> {
+    fn as_ref(&self) -> &Node<T, E> {
         self.0.as_ref().unwrap()
     }
-    fn as_mut(&mut self) -> &mut Box<Node<T>> {
+    fn as_mut(&mut self) -> &mut Node<T, E> {
         self.0.as_mut().unwrap()
     }
-    fn take(&mut self) -> Box<Node<T>> {
+    fn take(&mut self) -> Box<Node<T, E>> {
         self.0.take().unwrap()
     }
-    fn unwrap(self) -> Box<Node<T>> {
+    fn unwrap(self) -> Box<Node<T, E>> {
         self.0.unwrap()
     }
     fn sum(&self) -> T {
@@ -98,109 +135,225 @@ impl<T: Monoid> SplayTree<T> {
             T::identity()
         }
     }
+    fn rsum(&self) -> T {
+        if let Some(ref x) = self.0 {
+            x.rsum.clone()
+        } else {
+            T::identity()
+        }
+    }
+    fn apply_all(&mut self, e: &E) {
+        if let Some(ref mut x) = self.0 {
+            x.data = e.act(&x.data);
+            x.sum = e.act(&x.sum);
+            x.rsum = e.act(&x.rsum);
+            x.lazy = E::op(x.lazy.clone(), e.clone());
+        }
+    }
+    fn toggle(&mut self) {
+        if let Some(ref mut x) = self.0 {
+            swap(&mut x.left, &mut x.right);
+            swap(&mut x.sum, &mut x.rsum);
+            x.rev ^= true;
+        }
+    }
     fn splay(&mut self, k: usize) {
         if self.is_none() {
             return;
         }
-        let mut left_tree: SplayTree<T> = Self(Some(Box::new(Node::new(T::identity()))));
-        let mut right_tree: SplayTree<T> = Self(Some(Box::new(Node::new(T::identity()))));
-        let mut lnode = left_tree.as_mut();
-        let mut rnode = right_tree.as_mut();
+        // 左右に切り離したノードを積んでおいて、最後に組み立てます。
+        let mut lefts: Vec<Box<Node<T, E>>> = Vec::new();
+        let mut rights: Vec<Box<Node<T, E>>> = Vec::new();
         let mut now = self.take();
+        // nowの部分木より左にある要素の個数
         let mut cnt = 0;
         loop {
-            if k == now.left.size() + cnt {
-                break;
-            } else if k < now.left.size() + cnt {
+            now.push();
+            let pos = cnt + now.left.size();
+            if k < pos {
                 // 左の方に潜ります。
-                if now.left.is_none() {
-                    break;
-                }
-                if k < now.left.as_ref().left.size() + cnt - 1 {
-                    now = Self::rotr(now);
-                    if now.left.is_none() {
-                        break;
-                    }
+                let mut l = now.left.take();
+                l.push();
+                if k < cnt + l.left.size() {
+                    now.left = replace(&mut l.right, Self::none());
+                    now.update();
+                    l.right = Self(Some(now));
+                    now = l;
+                    l = now.left.take();
                 }
-                let mut nl = now.left.take();
-                swap(&mut now, &mut nl);
-                nl.update();
-                rnode.left = Self(Some(nl));
-                rnode.update();
-                rnode = rnode.left.as_mut();
-            } else {
+                rights.push(now);
+                now = l;
+            } else if k > pos {
                 // 右の方に潜ります。
-                cnt += now.left.size() + 1;
-                if now.right.is_none() {
-                    break;
+                cnt = pos + 1;
+                let mut r = now.right.take();
+                r.push();
+                if k > cnt + r.left.size() {
+                    cnt += r.left.size() + 1;
+                    now.right = replace(&mut r.left, Self::none());
+                    now.update();
+                    r.left = Self(Some(now));
+                    now = r;
+                    r = now.right.take();
                 }
-                if k > now.right.as_ref().left.size() + cnt + 1 {
-                    now = Self::rotl(now);
-                    if now.right.is_none() {
-                        break;
-                    }
-                }
-                let mut nr = now.right.take();
-                swap(&mut now, &mut nr);
-                nr.update();
-                lnode.right = Self(Some(nr));
-                lnode.update();
-                lnode = lnode.right.as_mut();
+                lefts.push(now);
+                now = r;
+            } else {
+                break;
             }
         }
-        rnode.left = now.right;
-        rnode.update();
-        lnode.right = now.left;
-        lnode.update();
-        now.left = left_tree.take().right;
-        now.right = right_tree.take().left;
+        let mut left = replace(&mut now.left, Self::none());
+        while let Some(mut x) = lefts.pop() {
+            x.right = left;
+            x.update();
+            left = Self(Some(x));
+        }
+        let mut right = replace(&mut now.right, Self::none());
+        while let Some(mut x) = rights.pop() {
+            x.left = right;
+            x.update();
+            right = Self(Some(x));
+        }
+        now.left = left;
+        now.right = right;
         now.update();
         *self = Self(Some(now));
     }
-    fn rotr(mut root: Box<Node<T>>) -> Box<Node<T>> {
-        let mut new_root = root.left.take();
-        root.left = new_root.right;
-        root.update();
-        new_root.right = Self(Some(root));
-        new_root.update();
-        new_root
-    }
-    fn rotl(mut root: Box<Node<T>>) -> Box<Node<T>> {
-        let mut new_root = root.right.take();
-        root.right = new_root.left;
-        root.update();
-        new_root.left = Self(Some(root));
-        new_root.update();
-        new_root
-    }
 }
-#[derive(Debug)]
-struct Node<T: Monoid> {
-    left: SplayTree<T>,
-    right: SplayTree<T>,
+struct Node<T: Monoid, E: Act<T>> {
+    left: SplayTree<T, E>,
+    right: SplayTree<T, E>,
     size: usize,
     data: T,
     sum: T,
+    /// 逆順の積
+    rsum: T,
+    lazy: E,
+    rev: bool,
 }
-impl<T: Monoid> Node<T> {
+impl<T: Monoid, E: Act<T>> Node<T, E> {
     fn new(init: T) -> Self {
         Self {
             left: SplayTree::none(),
             right: SplayTree::none(),
             size: 1,
             data: init.clone(),
-            sum: init,
+            sum: init.clone(),
+            rsum: init,
+            lazy: E::identity(),
+            rev: false,
         }
     }
     fn update(&mut self) {
         self.size = self.left.size() + self.right.size() + 1;
         self.sum = T::op(T::op(self.left.sum(), self.data.clone()), self.right.sum());
+        self.rsum = T::op(
+            T::op(self.right.rsum(), self.data.clone()),
+            self.left.rsum(),
+        );
+    }
+    fn push(&mut self) {
+        if self.lazy != E::identity() {
+            let e = replace(&mut self.lazy, E::identity());
+            self.left.apply_all(&e);
+            self.right.apply_all(&e);
+        }
+        if self.rev {
+            self.left.toggle();
+            self.right.toggle();
+            self.rev = false;
+        }
+    }
+}
+/// 中順に値を返すやつ。遅延している作用と反転はその場で反映します。
+pub struct Iter<'a, T: Monoid, E: Act<T>> {
+    // (ノード, 上から降ってくる作用, 反転しているか)
+    stack: Vec<(&'a Node<T, E>, E, bool)>,
+}
+impl<'a, T: Monoid, E: Act<T>> Iter<'a, T, E> {
+    fn push_spine(&mut self, mut now: &'a SplayTree<T, E>, mut e: E, mut rev: bool) {
+        while let Some(ref node) = now.0 {
+            let child_e = E::op(node.lazy.clone(), e.clone());
+            let child_rev = rev ^ node.rev;
+            self.stack.push((&**node, e, rev));
+            now = if rev { &node.right } else { &node.left };
+            e = child_e;
+            rev = child_rev;
+        }
+    }
+}
+impl<'a, T: Monoid, E: Act<T>> Iterator for Iter<'a, T, E> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let (node, e, rev) = self.stack.pop()?;
+        let ret = e.act(&node.data);
+        let next = if rev { &node.left } else { &node.right };
+        self.push_spine(next, E::op(node.lazy.clone(), e), rev ^ node.rev);
+        Some(ret)
+    }
+}
+/// 中身を取り出しながら中順に返すやつ
+pub struct IntoIter<T: Monoid, E: Act<T>> {
+    stack: Vec<Box<Node<T, E>>>,
+}
+impl<T: Monoid, E: Act<T>> IntoIter<T, E> {
+    fn push_spine(&mut self, mut now: SplayTree<T, E>) {
+        while let Some(mut node) = now.0 {
+            node.push();
+            now = replace(&mut node.left, SplayTree::none());
+            self.stack.push(node);
+        }
+    }
+}
+impl<T: Monoid, E: Act<T>> Iterator for IntoIter<T, E> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        let mut node = self.stack.pop()?;
+        self.push_spine(replace(&mut node.right, SplayTree::none()));
+        Some(node.data)
+    }
+}
+impl<T: Monoid, E: Act<T>> IntoIterator for SplayTree<T, E> {
+    type Item = T;
+    type IntoIter = IntoIter<T, E>;
+    fn into_iter(self) -> IntoIter<T, E> {
+        let mut ret = IntoIter { stack: Vec::new() };
+        ret.push_spine(self);
+        ret
+    }
+}
+impl<'a, T: Monoid, E: Act<T>> IntoIterator for &'a SplayTree<T, E> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, E>;
+    fn into_iter(self) -> Iter<'a, T, E> {
+        self.iter()
+    }
+}
+impl<T: Monoid, E: Act<T>> FromIterator<T> for SplayTree<T, E> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        //! O(n)で釣り合った木を作ります。
+        let data: Vec<T> = iter.into_iter().collect();
+        Self::build(&data, 0..data.len())
+    }
+}
+impl<T: Monoid, E: Act<T>> Extend<T> for SplayTree<T, E> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        //! 末尾に追加します。O(k + log n)
+        let sel = replace(self, Self::none());
+        *self = Self::merge(sel, iter.into_iter().collect());
+    }
+}
+impl<T: Monoid, E: Act<T>> fmt::Debug for SplayTree<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra_struct::AddMonoid;
+    use algebra::*;
+    use algebra_struct::{AddMonoid, Ends};
+    use xorshift::Xorshift256;
     #[test]
     fn splay_tree_test() {
         let mut st: SplayTree<AddMonoid> = SplayTree::none();
@@ -213,4 +366,83 @@ mod tests {
         assert_eq!(st.query(0..6), AddMonoid(63));
         eprintln!("{:#?}", st);
     }
+    #[test]
+    fn lazy_reverse_test() {
+        let mut rng = Xorshift256::new();
+        let mut st: SplayTree<Ends, i64> = SplayTree::none();
+        let mut naive: Vec<i64> = Vec::new();
+        for _ in 0..3000 {
+            let n = naive.len();
+            let l = rng.gen() as usize % (n + 1);
+            let r = l + rng.gen() as usize % (n - l + 1);
+            match rng.gen() % 6 {
+                0 | 1 => {
+                    let x = (rng.gen() % 100) as i64;
+                    st.insert(l, Ends::leaf(x));
+                    naive.insert(l, x);
+                }
+                2 => {
+                    if l < n {
+                        assert_eq!(st.erase(l), Ends::leaf(naive.remove(l)));
+                    }
+                }
+                3 => {
+                    let e = (rng.gen() % 10) as i64;
+                    st.apply(l..r, e);
+                    for x in &mut naive[l..r] {
+                        *x += e;
+                    }
+                }
+                4 => {
+                    st.reverse(l..r);
+                    naive[l..r].reverse();
+                }
+                _ => {
+                    let got = st.query(l..r);
+                    let expected = naive[l..r]
+                        .iter()
+                        .fold(Ends::identity(), |acc, &x| Ends::op(acc, Ends::leaf(x)));
+                    assert_eq!(got, expected);
+                    if l < n {
+                        assert_eq!(st.access(l).sum, naive[l]);
+                    }
+                }
+            }
+            assert_eq!(st.size(), naive.len());
+        }
+    }
+    #[test]
+    fn iter_test() {
+        let mut rng = Xorshift256::new();
+        let mut naive: Vec<i64> = (0..50).collect();
+        let mut st: SplayTree<Ends, i64> = naive.iter().map(|&x| Ends::leaf(x)).collect();
+        for _ in 0..300 {
+            let n = naive.len();
+            let l = rng.gen() as usize % (n + 1);
+            let r = l + rng.gen() as usize % (n - l + 1);
+            if rng.gen() & 1 == 0 {
+                let e = (rng.gen() % 10) as i64;
+                st.apply(l..r, e);
+                for x in &mut naive[l..r] {
+                    *x += e;
+                }
+            } else {
+                st.reverse(l..r);
+                naive[l..r].reverse();
+            }
+            let got: Vec<_> = st.iter().map(|x| x.sum).collect();
+            assert_eq!(got, naive);
+        }
+        st.extend((100..110).map(Ends::leaf));
+        naive.extend(100..110);
+        let got: Vec<_> = (&st).into_iter().map(|x| x.sum).collect();
+        assert_eq!(got, naive);
+        let got: Vec<_> = st.into_iter().map(|x| x.sum).collect();
+        assert_eq!(got, naive);
+        let small: SplayTree<AddMonoid> = (1..4).map(AddMonoid).collect();
+        assert_eq!(
+            format!("{:?}", small),
+            format!("{:?}", vec![AddMonoid(1), AddMonoid(2), AddMonoid(3)])
+        );
+    }
 }
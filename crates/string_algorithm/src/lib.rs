@@ -0,0 +1,433 @@
+const NONE: usize = !0;
+
+pub fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    //! SA-IS. 0 <= s[i] <= upper
+    let n = s.len();
+    match n {
+        0 => return vec![],
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => (),
+    }
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[s[i]] += 1;
+        } else {
+            sum_l[s[i] + 1] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+    let induce = |lms: &[usize], sa: &mut [usize]| {
+        sa.iter_mut().for_each(|x| *x = NONE);
+        let mut buf = sum_s.clone();
+        for &d in lms {
+            if d == n {
+                continue;
+            }
+            sa[buf[s[d]]] = d;
+            buf[s[d]] += 1;
+        }
+        let mut buf = sum_l.clone();
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != NONE && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        let mut buf = sum_l.clone();
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != NONE && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+    };
+    let mut lms_map = vec![NONE; n + 1];
+    let mut lms = Vec::new();
+    for i in 1..n {
+        if !ls[i - 1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let m = lms.len();
+    let mut sa = vec![NONE; n];
+    induce(&lms, &mut sa);
+    if m > 0 {
+        let mut sorted_lms: Vec<_> = sa.iter().copied().filter(|&v| lms_map[v] != NONE).collect();
+        let mut rec_s = vec![0; m];
+        let mut rec_upper = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = if lms_map[l] + 1 < m {
+                lms[lms_map[l] + 1]
+            } else {
+                n
+            };
+            let end_r = if lms_map[r] + 1 < m {
+                lms[lms_map[r] + 1]
+            } else {
+                n
+            };
+            let mut same = true;
+            if end_l - l != end_r - r {
+                same = false;
+            } else {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                if l == n || s[l] != s[r] {
+                    same = false;
+                }
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = sa_is(&rec_s, rec_upper);
+        for (x, &i) in sorted_lms.iter_mut().zip(rec_sa.iter()) {
+            *x = lms[i];
+        }
+        induce(&sorted_lms, &mut sa);
+    }
+    sa
+}
+
+pub fn suffix_array<T: Ord>(s: &[T]) -> Vec<usize> {
+    //! O(n log n) (座圧) + O(n) (SA-IS)
+    let mut vals: Vec<&T> = s.iter().collect();
+    vals.sort();
+    vals.dedup();
+    let s2: Vec<usize> = s.iter().map(|x| vals.binary_search(&x).unwrap()).collect();
+    sa_is(&s2, vals.len().saturating_sub(1))
+}
+
+pub fn lcp_array<T: Eq>(s: &[T], sa: &[usize]) -> Vec<usize> {
+    //! Kasai. lcp[i] = lcp(s[sa[i]..], s[sa[i + 1]..])
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut rank = vec![0; n];
+    for (i, &x) in sa.iter().enumerate() {
+        rank[x] = i;
+    }
+    let mut lcp = vec![0; n - 1];
+    let mut h: usize = 0;
+    for i in 0..n {
+        h = h.saturating_sub(1);
+        if rank[i] == 0 {
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while j + h < n && i + h < n && s[j + h] == s[i + h] {
+            h += 1;
+        }
+        lcp[rank[i] - 1] = h;
+    }
+    lcp
+}
+
+pub fn z_algorithm<T: Eq>(s: &[T]) -> Vec<usize> {
+    //! z[i] = lcp(s, s[i..])
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    let mut z = vec![0; n];
+    z[0] = n;
+    let (mut i, mut j) = (1, 0);
+    while i < n {
+        while i + j < n && s[j] == s[i + j] {
+            j += 1;
+        }
+        z[i] = j;
+        if j == 0 {
+            i += 1;
+            continue;
+        }
+        let mut k = 1;
+        while i + k < n && k + z[k] < j {
+            z[i + k] = z[k];
+            k += 1;
+        }
+        i += k;
+        j -= k;
+    }
+    z
+}
+
+pub fn prefix_function<T: Eq>(s: &[T]) -> Vec<usize> {
+    //! pi[i] = s[..=i]の最長の真のborderの長さ
+    let n = s.len();
+    let mut pi = vec![0; n];
+    for i in 1..n {
+        let mut j = pi[i - 1];
+        while j > 0 && s[i] != s[j] {
+            j = pi[j - 1];
+        }
+        if s[i] == s[j] {
+            j += 1;
+        }
+        pi[i] = j;
+    }
+    pi
+}
+
+pub fn kmp_search<T: Eq>(text: &[T], pattern: &[T]) -> Vec<usize> {
+    //! return: patternが現れる位置(先頭)の列
+    let m = pattern.len();
+    if m == 0 {
+        return (0..=text.len()).collect();
+    }
+    let pi = prefix_function(pattern);
+    let mut ret = Vec::new();
+    let mut j = 0;
+    for (i, c) in text.iter().enumerate() {
+        while j > 0 && *c != pattern[j] {
+            j = pi[j - 1];
+        }
+        if *c == pattern[j] {
+            j += 1;
+        }
+        if j == m {
+            ret.push(i + 1 - m);
+            j = pi[j - 1];
+        }
+    }
+    ret
+}
+
+pub fn manacher<T: Eq>(s: &[T]) -> Vec<usize> {
+    //! rad[i]: s[i + 1 - rad[i]..i + rad[i]]がiを中心とする最長の奇数長回文
+    let n = s.len();
+    let mut rad = vec![0; n];
+    let (mut i, mut j) = (0, 0);
+    while i < n {
+        while i >= j && i + j < n && s[i - j] == s[i + j] {
+            j += 1;
+        }
+        rad[i] = j;
+        let mut k = 1;
+        while i >= k && k + rad[i - k] < j {
+            rad[i + k] = rad[i - k];
+            k += 1;
+        }
+        i += k;
+        j -= k;
+    }
+    rad
+}
+
+pub fn longest_palindromes<T: Eq>(s: &[T]) -> Vec<usize> {
+    //! ret[i]: 中心がi/2 (iが奇数なら文字と文字の間) の最長回文の長さ。長さは2n-1です。
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    let t: Vec<Option<&T>> = (0..2 * n - 1)
+        .map(|i| if i & 1 == 0 { Some(&s[i >> 1]) } else { None })
+        .collect();
+    manacher(&t)
+        .into_iter()
+        .enumerate()
+        .map(|(i, r)| {
+            if i & 1 == 0 {
+                (r - 1) / 2 * 2 + 1
+            } else {
+                r / 2 * 2
+            }
+        })
+        .collect()
+}
+
+pub fn run_enumerate<T: Eq>(s: &[T]) -> Vec<(usize, usize, usize)> {
+    //! Main-Lorentz. O(n log n)
+    //! return: (最小周期t, l, r) の列。(t, l, r)の辞書順です。
+    fn dfs<T: Eq>(s: &[T], l: usize, r: usize, runs: &mut Vec<(usize, usize, usize)>) {
+        if r - l <= 1 {
+            return;
+        }
+        let m = (l + r) / 2;
+        dfs(s, l, m, runs);
+        dfs(s, m, r, runs);
+        let rev_x: Vec<Option<&T>> = s[l..m].iter().rev().map(Some).collect();
+        let y: Vec<Option<&T>> = s[m..r].iter().map(Some).collect();
+        // 周期の始まりが右半分にあるもの
+        let z_y = z_algorithm(&y);
+        let mut t = rev_x.clone();
+        t.push(None);
+        t.extend(s[l..r].iter().rev().map(Some));
+        let z_t = z_algorithm(&t);
+        for p in 1..=r - m {
+            let a = if p < r - m { z_y[p] } else { 0 };
+            let b = z_t[m - l + 1 + r - m - p].min(m - l);
+            if b >= 1 && a + b >= p {
+                runs.push((p, m - b, m + p + a));
+            }
+        }
+        // 周期の終わりが左半分にあるもの
+        let z_x = z_algorithm(&rev_x);
+        let mut t = y;
+        t.push(None);
+        t.extend(s[l..r].iter().map(Some));
+        let z_t = z_algorithm(&t);
+        for p in 1..=m - l {
+            let a = if p < m - l { z_x[p] } else { 0 };
+            let b = z_t[r - m + 1 + m - p - l].min(r - m);
+            if b >= 1 && a + b >= p {
+                runs.push((p, m - p - a, m + b));
+            }
+        }
+    }
+    let mut runs = Vec::new();
+    dfs(s, 0, s.len(), &mut runs);
+    // 途中で見つかった極大でないものを取り除きます。
+    runs.sort_by(|x, y| (x.0, x.1, y.2).cmp(&(y.0, y.1, x.2)));
+    let mut maximal: Vec<(usize, usize, usize)> = Vec::new();
+    for (p, l, r) in runs {
+        match maximal.last() {
+            Some(&(q, _, rr)) if q == p && r <= rr => (),
+            _ => maximal.push((p, l, r)),
+        }
+    }
+    // 同じ区間については最小周期のみ残します。
+    maximal.sort_by_key(|&(p, l, r)| (l, r, p));
+    maximal.dedup_by_key(|&mut (_, l, r)| (l, r));
+    maximal.sort();
+    maximal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    fn random_string(rng: &mut Xorshift256, n: usize, sigma: u64) -> Vec<u8> {
+        (0..n).map(|_| b'a' + (rng.gen() % sigma) as u8).collect()
+    }
+    #[test]
+    fn suffix_array_test() {
+        let mut rng = Xorshift256::new();
+        for n in 0..60 {
+            for &sigma in &[1, 2, 3, 26] {
+                let s = random_string(&mut rng, n, sigma);
+                let mut naive: Vec<usize> = (0..n).collect();
+                naive.sort_by_key(|&i| &s[i..]);
+                let sa = suffix_array(&s);
+                assert_eq!(sa, naive);
+                let lcp = lcp_array(&s, &sa);
+                for i in 0..n.saturating_sub(1) {
+                    let (a, b) = (&s[sa[i]..], &s[sa[i + 1]..]);
+                    let l = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+                    assert_eq!(lcp[i], l);
+                }
+            }
+        }
+        let s = vec![3, -1, 4, -1, 5, -9];
+        assert_eq!(suffix_array(&s), vec![5, 1, 3, 0, 2, 4]);
+    }
+    #[test]
+    fn matching_test() {
+        let mut rng = Xorshift256::new();
+        for n in 1..40 {
+            let s = random_string(&mut rng, n, 2);
+            let z = z_algorithm(&s);
+            let pi = prefix_function(&s);
+            for i in 0..n {
+                let l = s
+                    .iter()
+                    .zip(s[i..].iter())
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                assert_eq!(z[i], l);
+                let b = (0..=i)
+                    .filter(|&k| s[..k] == s[i + 1 - k..=i])
+                    .max()
+                    .unwrap();
+                assert_eq!(pi[i], b);
+            }
+            let pattern = random_string(&mut rng, 3, 2);
+            let naive: Vec<usize> = (0..n.saturating_sub(2))
+                .filter(|&i| s[i..i + 3] == pattern[..])
+                .collect();
+            assert_eq!(kmp_search(&s, &pattern), naive);
+        }
+    }
+    #[test]
+    fn palindrome_test() {
+        let mut rng = Xorshift256::new();
+        let is_palindrome = |s: &[u8]| s.iter().eq(s.iter().rev());
+        for n in 1..40 {
+            let s = random_string(&mut rng, n, 2);
+            let rad = manacher(&s);
+            let len = longest_palindromes(&s);
+            for c in 0..2 * n - 1 {
+                let naive = (0..=n)
+                    .filter(|&k| {
+                        k % 2 == (c + 1) % 2
+                            && c + 1 >= k
+                            && (c + 1 - k) / 2 + k <= n
+                            && is_palindrome(&s[(c + 1 - k) / 2..(c + 1 - k) / 2 + k])
+                    })
+                    .max()
+                    .unwrap();
+                assert_eq!(len[c], naive);
+                if c % 2 == 0 {
+                    assert_eq!(rad[c / 2] * 2 - 1, naive);
+                }
+            }
+        }
+    }
+    #[test]
+    fn run_enumerate_test() {
+        let mut rng = Xorshift256::new();
+        for n in 0..50 {
+            for &sigma in &[1, 2, 3] {
+                let s = random_string(&mut rng, n, sigma);
+                let mut naive = Vec::new();
+                for p in 1..=n / 2 {
+                    let mut i = 0;
+                    while i + p < n {
+                        if s[i] != s[i + p] {
+                            i += 1;
+                            continue;
+                        }
+                        let mut j = i;
+                        while j + p < n && s[j] == s[j + p] {
+                            j += 1;
+                        }
+                        if j + p - i >= 2 * p {
+                            naive.push((p, i, j + p));
+                        }
+                        i = j;
+                    }
+                }
+                naive.sort_by_key(|&(p, l, r)| (l, r, p));
+                naive.dedup_by_key(|&mut (_, l, r)| (l, r));
+                naive.sort();
+                assert_eq!(run_enumerate(&s), naive, "{:?}", s);
+            }
+        }
+    }
+}
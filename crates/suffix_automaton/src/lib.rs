@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+const NONE: usize = !0;
+
+struct Node {
+    next: BTreeMap<u8, usize>,
+    link: usize,
+    len: usize,
+    cnt: usize,
+}
+impl Node {
+    fn new(len: usize, cnt: usize) -> Self {
+        Self {
+            next: BTreeMap::new(),
+            link: NONE,
+            len,
+            cnt,
+        }
+    }
+}
+
+pub struct SuffixAutomaton {
+    nodes: Vec<Node>,
+    last: usize,
+    distinct: u64,
+}
+impl SuffixAutomaton {
+    pub fn new(s: &[u8]) -> Self {
+        let mut ret = Self {
+            nodes: vec![Node::new(0, 0)],
+            last: 0,
+            distinct: 0,
+        };
+        for &c in s {
+            ret.push(c);
+        }
+        ret
+    }
+    pub fn push(&mut self, c: u8) {
+        //! 償却O(log σ)
+        let cur = self.nodes.len();
+        self.nodes.push(Node::new(self.nodes[self.last].len + 1, 1));
+        let mut p = self.last;
+        while p != NONE && !self.nodes[p].next.contains_key(&c) {
+            self.nodes[p].next.insert(c, cur);
+            p = self.nodes[p].link;
+        }
+        if p == NONE {
+            self.nodes[cur].link = 0;
+        } else {
+            let q = self.nodes[p].next[&c];
+            if self.nodes[p].len + 1 == self.nodes[q].len {
+                self.nodes[cur].link = q;
+            } else {
+                let clone = self.nodes.len();
+                let mut node = Node::new(self.nodes[p].len + 1, 0);
+                node.next = self.nodes[q].next.clone();
+                node.link = self.nodes[q].link;
+                self.nodes.push(node);
+                while p != NONE && self.nodes[p].next.get(&c) == Some(&q) {
+                    self.nodes[p].next.insert(c, clone);
+                    p = self.nodes[p].link;
+                }
+                self.nodes[q].link = clone;
+                self.nodes[cur].link = clone;
+            }
+        }
+        self.last = cur;
+        self.distinct += (self.nodes[cur].len - self.nodes[self.nodes[cur].link].len) as u64;
+    }
+    pub fn size(&self) -> usize {
+        //! 状態数
+        self.nodes.len()
+    }
+    pub fn len(&self, state: usize) -> usize {
+        //! stateが表す最長の文字列の長さ
+        self.nodes[state].len
+    }
+    pub fn link(&self, state: usize) -> Option<usize> {
+        match self.nodes[state].link {
+            NONE => None,
+            x => Some(x),
+        }
+    }
+    pub fn next(&self, state: usize, c: u8) -> Option<usize> {
+        self.nodes[state].next.get(&c).copied()
+    }
+    pub fn last(&self) -> usize {
+        self.last
+    }
+    pub fn distinct_substrings(&self) -> u64 {
+        //! 空文字列を含みません。
+        self.distinct
+    }
+    pub fn topological_order(&self) -> Vec<usize> {
+        //! lenの昇順 (counting sort)
+        let n = self.nodes.len();
+        let max = self.nodes[self.last].len;
+        let mut cnt = vec![0; max + 2];
+        for v in &self.nodes {
+            cnt[v.len + 1] += 1;
+        }
+        for i in 0..=max {
+            cnt[i + 1] += cnt[i];
+        }
+        let mut order = vec![0; n];
+        for (i, v) in self.nodes.iter().enumerate() {
+            order[cnt[v.len]] = i;
+            cnt[v.len] += 1;
+        }
+        order
+    }
+    pub fn endpos_sizes(&self) -> Vec<usize> {
+        //! 各状態の文字列の出現回数
+        let mut ret: Vec<usize> = self.nodes.iter().map(|v| v.cnt).collect();
+        for &v in self.topological_order().iter().skip(1).rev() {
+            ret[self.nodes[v].link] += ret[v];
+        }
+        ret
+    }
+    pub fn state_of(&self, pattern: &[u8]) -> Option<usize> {
+        let mut now = 0;
+        for c in pattern {
+            now = *self.nodes[now].next.get(c)?;
+        }
+        Some(now)
+    }
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.state_of(pattern).is_some()
+    }
+    pub fn longest_common_substring(&self, t: &[u8]) -> (usize, usize) {
+        //! return: (tでの開始位置, 長さ)
+        let (mut now, mut len) = (0, 0);
+        let (mut best_start, mut best_len) = (0, 0);
+        for (i, c) in t.iter().enumerate() {
+            while now != 0 && !self.nodes[now].next.contains_key(c) {
+                now = self.nodes[now].link;
+                len = self.nodes[now].len;
+            }
+            if let Some(&x) = self.nodes[now].next.get(c) {
+                now = x;
+                len += 1;
+            }
+            if len > best_len {
+                best_len = len;
+                best_start = i + 1 - len;
+            }
+        }
+        (best_start, best_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeSet, HashMap};
+    use xorshift::Xorshift256;
+    #[test]
+    fn suffix_automaton_test() {
+        let mut rng = Xorshift256::new();
+        for n in 0..40 {
+            let s: Vec<u8> = (0..n).map(|_| b'a' + (rng.gen() % 3) as u8).collect();
+            let sam = SuffixAutomaton::new(&s);
+            let mut occ = HashMap::new();
+            for i in 0..n {
+                for j in i + 1..=n {
+                    *occ.entry(&s[i..j]).or_insert(0) += 1;
+                }
+            }
+            assert_eq!(sam.distinct_substrings(), occ.len() as u64);
+            assert!(sam.size() <= 2 * n.max(1));
+            let endpos = sam.endpos_sizes();
+            for (t, &c) in occ.iter() {
+                assert_eq!(endpos[sam.state_of(t).unwrap()], c);
+            }
+            assert!(!sam.contains(b"d"));
+            let t: Vec<u8> = (0..20).map(|_| b'a' + (rng.gen() % 3) as u8).collect();
+            let (start, len) = sam.longest_common_substring(&t);
+            assert!(sam.contains(&t[start..start + len]));
+            let subs: BTreeSet<&[u8]> = occ.keys().copied().collect();
+            let naive = (0..t.len())
+                .flat_map(|i| (i + 1..=t.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| subs.contains(&t[i..j]))
+                .map(|(i, j)| j - i)
+                .max()
+                .unwrap_or(0);
+            assert_eq!(len, naive);
+        }
+    }
+}
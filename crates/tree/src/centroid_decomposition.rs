@@ -0,0 +1,179 @@
+use graph::Graph;
+use std::collections::VecDeque;
+
+const NONE: usize = !0;
+
+#[derive(Clone, Debug)]
+pub struct CentroidComponent {
+    pub centroid: usize,
+    /// 重心分解の木での深さ
+    pub level: usize,
+    /// 成分の頂点。先頭が重心で、重心からの距離の順に並びます。
+    pub vertices: Vec<usize>,
+    /// verticesの各頂点の重心からの距離 (辺の本数)
+    pub depths: Vec<usize>,
+}
+
+/// 重心分解。成分を上から順に返すイテレータです。全体でO(n log n)
+pub struct CentroidDecomposition<'a, W> {
+    g: &'a Graph<W>,
+    removed: Vec<bool>,
+    stack: Vec<(usize, usize)>,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+impl<'a, W> CentroidDecomposition<'a, W> {
+    pub fn new(g: &'a Graph<W>) -> Self {
+        //! 森でも大丈夫です。
+        let n = g.size();
+        Self {
+            g,
+            removed: vec![false; n],
+            stack: (0..n).rev().map(|v| (v, 0)).collect(),
+            parent: vec![NONE; n],
+            size: vec![0; n],
+        }
+    }
+    fn bfs(&mut self, s: usize) -> Vec<usize> {
+        self.parent[s] = NONE;
+        let mut order = vec![s];
+        let mut i = 0;
+        while i < order.len() {
+            let v = order[i];
+            i += 1;
+            for e in &self.g[v] {
+                if e.to != self.parent[v] && !self.removed[e.to] {
+                    self.parent[e.to] = v;
+                    order.push(e.to);
+                }
+            }
+        }
+        order
+    }
+}
+impl<'a, W> Iterator for CentroidDecomposition<'a, W> {
+    type Item = CentroidComponent;
+    fn next(&mut self) -> Option<CentroidComponent> {
+        let (s, level) = loop {
+            let (s, level) = self.stack.pop()?;
+            // 森のときの初期値は、既に他の成分に含まれていることがあります。
+            if !self.removed[s] {
+                break (s, level);
+            }
+        };
+        let order = self.bfs(s);
+        let n = order.len();
+        let mut centroid = s;
+        for &v in order.iter().rev() {
+            self.size[v] = 1;
+            let mut max = 0;
+            for e in &self.g[v] {
+                if e.to != self.parent[v] && !self.removed[e.to] {
+                    self.size[v] += self.size[e.to];
+                    max = max.max(self.size[e.to]);
+                }
+            }
+            if 2 * max.max(n - self.size[v]) <= n {
+                centroid = v;
+            }
+        }
+        let mut vertices = Vec::with_capacity(n);
+        let mut depths = Vec::with_capacity(n);
+        let mut queue = VecDeque::new();
+        queue.push_back((centroid, NONE, 0));
+        while let Some((v, p, d)) = queue.pop_front() {
+            vertices.push(v);
+            depths.push(d);
+            for e in &self.g[v] {
+                if e.to != p && !self.removed[e.to] {
+                    queue.push_back((e.to, v, d + 1));
+                }
+            }
+        }
+        self.removed[centroid] = true;
+        for e in &self.g[centroid] {
+            if !self.removed[e.to] {
+                self.stack.push((e.to, level + 1));
+            }
+        }
+        Some(CentroidComponent {
+            centroid,
+            level,
+            vertices,
+            depths,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn centroid_decomposition_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 40;
+            // 森にするために、いくつかの辺を抜きます。
+            let edges: Vec<_> = (1..n)
+                .map(|v| (rng.gen() as usize % v, v, rng.gen() % 8))
+                .filter(|e| e.2 != 1)
+                .map(|e| (e.0, e.1))
+                .collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let mut seen = vec![false; n];
+            let mut level = vec![0; n];
+            let mut total = 0;
+            for c in CentroidDecomposition::new(&g) {
+                assert!(!seen[c.centroid]);
+                assert_eq!(c.vertices[0], c.centroid);
+                seen[c.centroid] = true;
+                level[c.centroid] = c.level;
+                total += c.vertices.len();
+                let in_comp = |v: usize| c.vertices.contains(&v);
+                let mut dist = vec![NONE; n];
+                dist[c.centroid] = 0;
+                let mut stack = vec![c.centroid];
+                while let Some(v) = stack.pop() {
+                    for e in &g[v] {
+                        if dist[e.to] == NONE && in_comp(e.to) {
+                            dist[e.to] = dist[v] + 1;
+                            stack.push(e.to);
+                        }
+                    }
+                }
+                for (&v, &d) in c.vertices.iter().zip(&c.depths) {
+                    // 上の重心は成分に含まれません。
+                    assert!(v == c.centroid || !seen[v]);
+                    assert_eq!(dist[v], d);
+                    // 成分は上の重心で区切られた連結成分です。
+                    assert!(g[v].iter().all(|e| in_comp(e.to) || seen[e.to]));
+                }
+                assert_eq!(
+                    dist.iter().filter(|&&d| d != NONE).count(),
+                    c.vertices.len()
+                );
+                // 重心を除いた各部分の大きさは半分以下
+                for e in &g[c.centroid] {
+                    if !in_comp(e.to) {
+                        continue;
+                    }
+                    let mut cnt = 0;
+                    let mut stack = vec![(e.to, c.centroid)];
+                    while let Some((v, p)) = stack.pop() {
+                        cnt += 1;
+                        for f in &g[v] {
+                            if f.to != p && in_comp(f.to) {
+                                stack.push((f.to, v));
+                            }
+                        }
+                    }
+                    assert!(2 * cnt <= c.vertices.len());
+                }
+            }
+            assert!(seen.iter().all(|&x| x));
+            assert!(level.iter().all(|&l| 1 << l <= n));
+            assert!(total <= n * (1 + 64 - n.leading_zeros() as usize));
+        }
+    }
+}
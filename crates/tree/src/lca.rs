@@ -0,0 +1,112 @@
+use graph::Graph;
+
+const NONE: usize = !0;
+
+/// オイラーツアーとSparse TableでO(1)のLCA
+pub struct EulerTourLca {
+    depth: Vec<usize>,
+    first: Vec<usize>,
+    /// table[k][i]はtour[i..i + 2^k]で深さ最小の頂点
+    table: Vec<Vec<usize>>,
+}
+impl EulerTourLca {
+    pub fn new<W>(g: &Graph<W>, root: usize) -> Self {
+        //! O(n log n)
+        let n = g.size();
+        let mut depth = vec![0; n];
+        let mut first = vec![NONE; n];
+        let mut tour = Vec::with_capacity(2 * n);
+        // (頂点, 親, 次に見る辺)
+        let mut call = vec![(root, NONE, 0)];
+        first[root] = 0;
+        tour.push(root);
+        while let Some(&mut (v, p, ref mut i)) = call.last_mut() {
+            if *i < g[v].len() {
+                let to = g[v][*i].to;
+                *i += 1;
+                if to != p {
+                    depth[to] = depth[v] + 1;
+                    first[to] = tour.len();
+                    tour.push(to);
+                    call.push((to, v, 0));
+                }
+            } else {
+                call.pop();
+                if p != NONE {
+                    tour.push(p);
+                }
+            }
+        }
+        let mut table = vec![tour];
+        let mut k = 1;
+        while 1 << k <= table[0].len() {
+            let prev = &table[k - 1];
+            let next = (0..table[0].len() + 1 - (1 << k))
+                .map(|i| {
+                    let (a, b) = (prev[i], prev[i + (1 << (k - 1))]);
+                    if depth[a] < depth[b] {
+                        a
+                    } else {
+                        b
+                    }
+                })
+                .collect();
+            table.push(next);
+            k += 1;
+        }
+        Self {
+            depth,
+            first,
+            table,
+        }
+    }
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        //! O(1)
+        let (l, r) = {
+            let (a, b) = (self.first[u], self.first[v]);
+            if a < b {
+                (a, b + 1)
+            } else {
+                (b, a + 1)
+            }
+        };
+        let k = (0usize.leading_zeros() - (r - l).leading_zeros() - 1) as usize;
+        let (a, b) = (self.table[k][l], self.table[k][r - (1 << k)]);
+        if self.depth[a] < self.depth[b] {
+            a
+        } else {
+            b
+        }
+    }
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.lca(u, v)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RootedTree;
+    use xorshift::Xorshift256;
+    #[test]
+    fn euler_tour_lca_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 40;
+            let edges: Vec<_> = (1..n).map(|v| (rng.gen() as usize % v, v)).collect();
+            let g = Graph::undirected_unweighted(n, &edges);
+            let root = rng.gen() as usize % n;
+            let t = RootedTree::new(&g, root);
+            let e = EulerTourLca::new(&g, root);
+            for u in 0..n {
+                for v in 0..n {
+                    assert_eq!(e.lca(u, v), t.lca(u, v));
+                    assert_eq!(e.dist(u, v), t.dist(u, v));
+                }
+            }
+        }
+    }
+}
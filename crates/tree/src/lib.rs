@@ -0,0 +1,283 @@
+use algebra::Zero;
+use graph::Graph;
+mod centroid_decomposition;
+mod lca;
+mod rerooting;
+pub use centroid_decomposition::*;
+pub use lca::*;
+pub use rerooting::*;
+
+const NONE: usize = !0;
+
+/// 根つき木。根の親はNoneです。
+pub struct RootedTree {
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    preorder: Vec<usize>,
+    /// ancestor[k][v]はvの2^k個上
+    ancestor: Vec<Vec<usize>>,
+}
+impl RootedTree {
+    pub fn new<W>(g: &Graph<W>, root: usize) -> Self {
+        //! 無向の木を渡してください。O(n log n)
+        let n = g.size();
+        let mut parent = vec![NONE; n];
+        let mut depth = vec![0; n];
+        let mut preorder = Vec::with_capacity(n);
+        let mut stack = vec![root];
+        while let Some(v) = stack.pop() {
+            preorder.push(v);
+            for e in &g[v] {
+                if e.to != parent[v] {
+                    parent[e.to] = v;
+                    depth[e.to] = depth[v] + 1;
+                    stack.push(e.to);
+                }
+            }
+        }
+        assert_eq!(preorder.len(), n);
+        let mut size = vec![1; n];
+        for &v in preorder.iter().skip(1).rev() {
+            size[parent[v]] += size[v];
+        }
+        let mut ancestor = vec![parent.clone()];
+        let mut k = 1;
+        while 1 << k < n {
+            let prev = &ancestor[k - 1];
+            let next = (0..n)
+                .map(|v| if prev[v] == NONE { NONE } else { prev[prev[v]] })
+                .collect();
+            ancestor.push(next);
+            k += 1;
+        }
+        Self {
+            parent,
+            depth,
+            size,
+            preorder,
+            ancestor,
+        }
+    }
+    pub fn size(&self) -> usize {
+        //! 頂点数
+        self.parent.len()
+    }
+    pub fn root(&self) -> usize {
+        self.preorder[0]
+    }
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        match self.parent[v] {
+            NONE => None,
+            p => Some(p),
+        }
+    }
+    pub fn depth(&self, v: usize) -> usize {
+        self.depth[v]
+    }
+    pub fn subtree_size(&self, v: usize) -> usize {
+        self.size[v]
+    }
+    pub fn preorder(&self) -> &[usize] {
+        //! 親が子より先に来る順番
+        &self.preorder
+    }
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        //! O(log n)
+        if k > self.depth[v] {
+            return None;
+        }
+        for (i, a) in self.ancestor.iter().enumerate() {
+            if k >> i & 1 == 1 {
+                v = a[v];
+            }
+        }
+        Some(v)
+    }
+    pub fn lca(&self, u: usize, v: usize) -> usize {
+        //! O(log n)
+        let (mut u, mut v) = if self.depth[u] < self.depth[v] {
+            (v, u)
+        } else {
+            (u, v)
+        };
+        u = self.kth_ancestor(u, self.depth[u] - self.depth[v]).unwrap();
+        if u == v {
+            return u;
+        }
+        for a in self.ancestor.iter().rev() {
+            if a[u] != a[v] {
+                u = a[u];
+                v = a[v];
+            }
+        }
+        self.parent[u]
+    }
+    pub fn dist(&self, u: usize, v: usize) -> usize {
+        //! 辺の本数
+        self.depth[u] + self.depth[v] - 2 * self.depth[self.lca(u, v)]
+    }
+    pub fn jump(&self, u: usize, v: usize, k: usize) -> Option<usize> {
+        //! uからvへのパスでk番目の頂点 (uが0番目)
+        let w = self.lca(u, v);
+        let (du, dv) = (self.depth[u] - self.depth[w], self.depth[v] - self.depth[w]);
+        if k <= du {
+            self.kth_ancestor(u, k)
+        } else if k <= du + dv {
+            self.kth_ancestor(v, du + dv - k)
+        } else {
+            None
+        }
+    }
+}
+
+fn farthest<W: Zero + Ord + Copy>(g: &Graph<W>, s: usize) -> (Vec<W>, Vec<usize>) {
+    let n = g.size();
+    let mut dist = vec![W::zero(); n];
+    let mut parent = vec![NONE; n];
+    let mut stack = vec![s];
+    while let Some(v) = stack.pop() {
+        for e in &g[v] {
+            if e.to != parent[v] && e.to != s {
+                parent[e.to] = v;
+                dist[e.to] = dist[v] + e.weight;
+                stack.push(e.to);
+            }
+        }
+    }
+    (dist, parent)
+}
+
+pub fn diameter<W: Zero + Ord + Copy>(g: &Graph<W>) -> (W, Vec<usize>) {
+    //! 重みは非負にしてください。O(n)
+    //! return: (長さ, 端から端までの頂点列)
+    let (dist, _) = farthest(g, 0);
+    let s = (0..g.size()).max_by_key(|&v| dist[v]).unwrap();
+    let (dist, parent) = farthest(g, s);
+    let t = (0..g.size()).max_by_key(|&v| dist[v]).unwrap();
+    let mut path = vec![t];
+    let mut v = t;
+    while parent[v] != NONE {
+        v = parent[v];
+        path.push(v);
+    }
+    (dist[t], path)
+}
+
+pub fn centroids<W>(g: &Graph<W>) -> Vec<usize> {
+    //! 重心 (1個か2個)。O(n)
+    let n = g.size();
+    let mut parent = vec![NONE; n];
+    let mut order = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        for e in &g[v] {
+            if e.to != parent[v] && e.to != 0 {
+                parent[e.to] = v;
+                stack.push(e.to);
+            }
+        }
+    }
+    let mut size = vec![1; n];
+    let mut ret = Vec::new();
+    for &v in order.iter().rev() {
+        // 子の部分木と親側がどれもn/2以下なら重心です。
+        let mut max = n - size[v];
+        for e in &g[v] {
+            if e.to != parent[v] {
+                max = max.max(size[e.to]);
+            }
+        }
+        if 2 * max <= n {
+            ret.push(v);
+        }
+        if parent[v] != NONE {
+            size[parent[v]] += size[v];
+        }
+    }
+    ret.sort();
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    fn random_tree(rng: &mut Xorshift256, n: usize) -> Vec<(usize, usize, i64)> {
+        (1..n)
+            .map(|v| (rng.gen() as usize % v, v, (rng.gen() % 10) as i64))
+            .collect()
+    }
+    #[test]
+    fn rooted_tree_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 30;
+            let edges = random_tree(&mut rng, n);
+            let g = Graph::undirected(n, &edges);
+            let root = rng.gen() as usize % n;
+            let t = RootedTree::new(&g, root);
+            let path_to_root = |mut v: usize| {
+                let mut path = vec![v];
+                while let Some(p) = t.parent(v) {
+                    path.push(p);
+                    v = p;
+                }
+                path
+            };
+            for u in 0..n {
+                let pu = path_to_root(u);
+                assert_eq!(t.depth(u), pu.len() - 1);
+                for (k, &a) in pu.iter().enumerate() {
+                    assert_eq!(t.kth_ancestor(u, k), Some(a));
+                }
+                assert_eq!(t.kth_ancestor(u, pu.len()), None);
+                let size = (0..n).filter(|&v| path_to_root(v).contains(&u)).count();
+                assert_eq!(t.subtree_size(u), size);
+                for v in 0..n {
+                    let pv = path_to_root(v);
+                    let w = *pu.iter().find(|x| pv.contains(x)).unwrap();
+                    assert_eq!(t.lca(u, v), w);
+                    // u -> w -> v のパス
+                    let mut path: Vec<_> = pu.iter().take_while(|&&x| x != w).copied().collect();
+                    let mut rest: Vec<_> = pv.iter().take_while(|&&x| x != w).copied().collect();
+                    path.push(w);
+                    rest.reverse();
+                    path.extend(rest);
+                    assert_eq!(t.dist(u, v), path.len() - 1);
+                    for (k, &x) in path.iter().enumerate() {
+                        assert_eq!(t.jump(u, v, k), Some(x));
+                    }
+                    assert_eq!(t.jump(u, v, path.len()), None);
+                }
+            }
+        }
+    }
+    #[test]
+    fn diameter_centroid_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 30;
+            let edges = random_tree(&mut rng, n);
+            let g = Graph::undirected(n, &edges);
+            let (d, path) = diameter(&g);
+            let naive = (0..n)
+                .map(|s| *farthest(&g, s).0.iter().max().unwrap())
+                .max();
+            assert_eq!(Some(d), naive);
+            let len: i64 = path
+                .windows(2)
+                .map(|w| g[w[0]].iter().find(|e| e.to == w[1]).unwrap().weight)
+                .sum();
+            assert_eq!(len, d);
+            let c = centroids(&g);
+            assert!(c.len() == 1 || c.len() == 2);
+            for v in 0..n {
+                let t = RootedTree::new(&g, v);
+                let max = g[v].iter().map(|e| t.subtree_size(e.to)).max().unwrap_or(0);
+                assert_eq!(c.contains(&v), 2 * max <= n);
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use algebra::Monoid;
+use graph::{Edge, Graph};
+
+const NONE: usize = !0;
+
+pub fn rerooting<W, T, U, F, G>(g: &Graph<W>, lift: F, add_root: G) -> Vec<U>
+where
+    T: Monoid + Copy,
+    U: Copy,
+    F: Fn(U, &Edge<W>) -> T,
+    G: Fn(T, usize) -> U,
+{
+    //! 全方位木DP。O(n)
+    //! dp[v] = add_root(子cについてのlift(dp[c], e)の積, v)
+    //! lift(x, e)のxはe.toの側の部分木の値で、eは新しい親からe.toへの辺です。
+    //! return: 各頂点を根にしたときのdp
+    let n = g.size();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut parent = vec![NONE; n];
+    let mut order = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(v) = stack.pop() {
+        order.push(v);
+        for e in &g[v] {
+            if e.to != parent[v] && e.to != 0 {
+                parent[e.to] = v;
+                stack.push(e.to);
+            }
+        }
+    }
+    // 0を根にしたときの部分木の値
+    let mut down: Vec<Option<U>> = vec![None; n];
+    for &v in order.iter().rev() {
+        let mut acc = T::identity();
+        for e in &g[v] {
+            if e.to != parent[v] {
+                acc.op_from_right(lift(down[e.to].unwrap(), e));
+            }
+        }
+        down[v] = Some(add_root(acc, v));
+    }
+    // 親の側の部分木の値
+    let mut up: Vec<Option<U>> = vec![None; n];
+    let mut ret = vec![None; n];
+    for &v in &order {
+        let vals: Vec<T> = g[v]
+            .iter()
+            .map(|e| {
+                if e.to == parent[v] {
+                    lift(up[v].unwrap(), e)
+                } else {
+                    lift(down[e.to].unwrap(), e)
+                }
+            })
+            .collect();
+        let k = vals.len();
+        let mut suffix = vec![T::identity(); k + 1];
+        for i in (0..k).rev() {
+            suffix[i] = T::op(vals[i], suffix[i + 1]);
+        }
+        ret[v] = Some(add_root(suffix[0], v));
+        let mut prefix = T::identity();
+        for (i, e) in g[v].iter().enumerate() {
+            if e.to != parent[v] {
+                up[e.to] = Some(add_root(T::op(prefix, suffix[i + 1]), v));
+            }
+            prefix.op_from_right(vals[i]);
+        }
+    }
+    ret.into_iter().map(|x| x.unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::*;
+    use xorshift::Xorshift256;
+    // (頂点数, 距離の和)
+    def_monoid! {
+        derive(Copy),
+        struct CountSum {
+            count: i64,
+            sum: i64,
+        },
+        CountSum { count: 0, sum: 0 },
+        fn op(lhs: CountSum, rhs: CountSum) -> CountSum {
+            CountSum {
+                count: lhs.count + rhs.count,
+                sum: lhs.sum + rhs.sum,
+            }
+        }
+    }
+    #[test]
+    fn rerooting_test() {
+        let mut rng = Xorshift256::new();
+        for _ in 0..50 {
+            let n = 1 + rng.gen() as usize % 30;
+            let edges: Vec<_> = (1..n)
+                .map(|v| (rng.gen() as usize % v, v, (rng.gen() % 10) as i64))
+                .collect();
+            let g = Graph::undirected(n, &edges);
+            // 全頂点への距離の和
+            let dp = rerooting(
+                &g,
+                |x: CountSum, e| CountSum {
+                    count: x.count,
+                    sum: x.sum + x.count * e.weight,
+                },
+                |acc, _| CountSum {
+                    count: acc.count + 1,
+                    sum: acc.sum,
+                },
+            );
+            for s in 0..n {
+                let mut dist = vec![None; n];
+                dist[s] = Some(0);
+                let mut stack = vec![s];
+                while let Some(v) = stack.pop() {
+                    for e in &g[v] {
+                        if dist[e.to].is_none() {
+                            dist[e.to] = Some(dist[v].unwrap() + e.weight);
+                            stack.push(e.to);
+                        }
+                    }
+                }
+                assert_eq!(dp[s].count, n as i64);
+                assert_eq!(dp[s].sum, dist.iter().map(|x| x.unwrap()).sum::<i64>());
+            }
+        }
+    }
+}
@@ -1,3 +1,7 @@
+use algebra::{Commut, Monoid};
+mod rollback;
+pub use rollback::*;
+
 pub struct Unionfind {
     par: Box<[i32]>,
     group_count: usize,
@@ -11,12 +15,18 @@ impl Unionfind {
     }
     pub fn find_root(&mut self, x: usize) -> usize {
         assert!(x < self.par.len());
-        if self.par[x] < 0 {
-            x
-        } else {
-            self.par[x] = self.find_root(self.par[x] as usize) as i32;
-            self.par[x] as usize
+        let mut root = x;
+        while self.par[root] >= 0 {
+            root = self.par[root] as usize;
         }
+        // 経路圧縮
+        let mut now = x;
+        while self.par[now] >= 0 {
+            let next = self.par[now] as usize;
+            self.par[now] = root as i32;
+            now = next;
+        }
+        root
     }
     pub fn unite(&mut self, x: usize, y: usize) -> Option<usize> {
         //! return: new root
@@ -46,4 +56,123 @@ impl Unionfind {
     pub fn get_num_of_groups(&self) -> usize {
         self.group_count
     }
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        //! 成分ごとの頂点のリスト。成分は最小の頂点の順、中身は昇順です。
+        let n = self.par.len();
+        let mut id = vec![!0; n];
+        let mut ret: Vec<Vec<usize>> = Vec::with_capacity(self.group_count);
+        for v in 0..n {
+            let r = self.find_root(v);
+            if id[r] == !0 {
+                id[r] = ret.len();
+                ret.push(Vec::with_capacity(self.get_group_size(r)));
+            }
+            ret[id[r]].push(v);
+        }
+        ret
+    }
+}
+
+/// 成分ごとにモノイドの値を持つUnionfind
+/// uniteすると値をopでまとめます。順番は決まらないので可換である必要があります。
+pub struct UnionfindWith<T: Commut + Monoid> {
+    uf: Unionfind,
+    data: Vec<T>,
+}
+impl<T: Commut + Monoid> UnionfindWith<T> {
+    pub fn new(n: usize) -> Self {
+        UnionfindWith {
+            uf: Unionfind::new(n),
+            data: vec![T::identity(); n],
+        }
+    }
+    pub fn new_with_init(init: &[T]) -> Self {
+        UnionfindWith {
+            uf: Unionfind::new(init.len()),
+            data: init.to_vec(),
+        }
+    }
+    pub fn find_root(&mut self, x: usize) -> usize {
+        self.uf.find_root(x)
+    }
+    pub fn unite(&mut self, x: usize, y: usize) -> Option<usize> {
+        //! return: new root
+        let (rx, ry) = (self.uf.find_root(x), self.uf.find_root(y));
+        let root = self.uf.unite(rx, ry)?;
+        let child = rx ^ ry ^ root;
+        let d = std::mem::replace(&mut self.data[child], T::identity());
+        self.data[root] = T::op(self.data[root].clone(), d);
+        Some(root)
+    }
+    pub fn is_same_group(&mut self, x: usize, y: usize) -> bool {
+        self.uf.is_same_group(x, y)
+    }
+    pub fn get_group_size(&mut self, x: usize) -> usize {
+        self.uf.get_group_size(x)
+    }
+    pub fn get_num_of_groups(&self) -> usize {
+        self.uf.get_num_of_groups()
+    }
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        self.uf.groups()
+    }
+    pub fn get(&mut self, x: usize) -> &T {
+        //! xの成分の値
+        let r = self.uf.find_root(x);
+        &self.data[r]
+    }
+    pub fn add(&mut self, x: usize, val: T) {
+        //! xの成分の値にvalをかけます。
+        let r = self.uf.find_root(x);
+        self.data[r] = T::op(self.data[r].clone(), val);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    #[test]
+    fn unionfind_test() {
+        let mut rng = Xorshift256::new();
+        let n = 50;
+        let init: Vec<i64> = (0..n).map(|_| (rng.gen() % 100) as i64).collect();
+        let mut uf = UnionfindWith::new_with_init(&init);
+        // 素朴に成分番号を持ちます。
+        let mut comp: Vec<usize> = (0..n).collect();
+        let mut extra = vec![0; n];
+        for _ in 0..200 {
+            let (u, v) = (rng.gen() as usize % n, rng.gen() as usize % n);
+            if rng.gen() % 3 == 1 {
+                let x = (rng.gen() % 100) as i64;
+                uf.add(u, x);
+                extra[u] += x;
+            } else {
+                let (cu, cv) = (comp[u], comp[v]);
+                assert_eq!(uf.unite(u, v).is_some(), cu != cv);
+                for c in comp.iter_mut() {
+                    if *c == cv {
+                        *c = cu;
+                    }
+                }
+            }
+            let mut expected: Vec<Vec<usize>> = vec![];
+            for c in 0..n {
+                let g: Vec<usize> = (0..n).filter(|&v| comp[v] == c).collect();
+                if !g.is_empty() {
+                    expected.push(g);
+                }
+            }
+            expected.sort();
+            assert_eq!(uf.groups(), expected);
+            assert_eq!(uf.get_num_of_groups(), expected.len());
+            for g in &expected {
+                let sum: i64 = g.iter().map(|&v| init[v] + extra[v]).sum();
+                for &v in g {
+                    assert_eq!(*uf.get(v), sum);
+                    assert_eq!(uf.get_group_size(v), g.len());
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,325 @@
+/// 経路圧縮をしないUnionfind
+/// 操作をさかのぼって取り消せます。各操作O(log n)です。
+pub struct RollbackUnionfind {
+    par: Box<[i32]>,
+    group_count: usize,
+    /// uniteごとに(根, 子, 子のもとのpar)。つながなかったときはNone
+    history: Vec<Option<(usize, usize, i32)>>,
+}
+impl RollbackUnionfind {
+    pub fn new(n: usize) -> Self {
+        RollbackUnionfind {
+            par: vec![-1; n].into_boxed_slice(),
+            group_count: n,
+            history: Vec::new(),
+        }
+    }
+    pub fn find_root(&self, mut x: usize) -> usize {
+        assert!(x < self.par.len());
+        while self.par[x] >= 0 {
+            x = self.par[x] as usize;
+        }
+        x
+    }
+    pub fn unite(&mut self, x: usize, y: usize) -> Option<usize> {
+        //! return: new root
+        //! つながなかったときも履歴には積まれるので、undoで1回分戻せます。
+        assert!(x < self.par.len() && y < self.par.len());
+        let mut rx = self.find_root(x);
+        let mut ry = self.find_root(y);
+        if rx == ry {
+            self.history.push(None);
+            return None;
+        }
+        if self.par[rx] > self.par[ry] {
+            std::mem::swap(&mut rx, &mut ry);
+        }
+        self.history.push(Some((rx, ry, self.par[ry])));
+        self.par[rx] += self.par[ry];
+        self.par[ry] = rx as i32;
+        self.group_count -= 1;
+        Some(rx)
+    }
+    pub fn undo(&mut self) -> bool {
+        //! 最後のuniteを取り消します。履歴がなければfalse
+        match self.history.pop() {
+            Some(Some((rx, ry, p))) => {
+                self.par[ry] = p;
+                self.par[rx] -= p;
+                self.group_count += 1;
+                true
+            }
+            Some(None) => true,
+            None => false,
+        }
+    }
+    pub fn snapshot(&self) -> usize {
+        //! 今の状態。rollbackに渡すとここまで戻ります。
+        self.history.len()
+    }
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(snapshot <= self.history.len());
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+    pub fn is_same_group(&self, x: usize, y: usize) -> bool {
+        assert!(x < self.par.len() && y < self.par.len());
+        self.find_root(x) == self.find_root(y)
+    }
+    pub fn get_group_size(&self, x: usize) -> usize {
+        (-self.par[self.find_root(x)]) as usize
+    }
+    pub fn get_num_of_groups(&self) -> usize {
+        self.group_count
+    }
+}
+
+enum Change {
+    Nothing,
+    Merge {
+        root: usize,
+        child: usize,
+        child_par: i32,
+        root_odd: bool,
+    },
+    Odd {
+        root: usize,
+        old: bool,
+    },
+}
+
+/// 根との偶奇も持つRollbackUnionfind
+/// 二部グラフ判定を取り消しながらやるときに使います。
+pub struct RollbackParityUnionfind {
+    par: Box<[i32]>,
+    /// 親との偶奇
+    parity: Box<[bool]>,
+    /// 根のところに、成分に奇閉路があるか
+    odd: Box<[bool]>,
+    group_count: usize,
+    odd_count: usize,
+    history: Vec<Change>,
+}
+impl RollbackParityUnionfind {
+    pub fn new(n: usize) -> Self {
+        RollbackParityUnionfind {
+            par: vec![-1; n].into_boxed_slice(),
+            parity: vec![false; n].into_boxed_slice(),
+            odd: vec![false; n].into_boxed_slice(),
+            group_count: n,
+            odd_count: 0,
+            history: Vec::new(),
+        }
+    }
+    pub fn find_root(&self, mut x: usize) -> (usize, bool) {
+        //! return: (根, 根との偶奇)
+        assert!(x < self.par.len());
+        let mut p = false;
+        while self.par[x] >= 0 {
+            p ^= self.parity[x];
+            x = self.par[x] as usize;
+        }
+        (x, p)
+    }
+    pub fn unite(&mut self, x: usize, y: usize, diff: bool) -> bool {
+        //! xとyの偶奇の差がdiffだという条件を足します。矛盾したらfalse
+        //! 二部グラフなら辺ごとにdiff = trueで呼びます。
+        let (mut rx, px) = self.find_root(x);
+        let (mut ry, py) = self.find_root(y);
+        if rx == ry {
+            if px ^ py == diff {
+                self.history.push(Change::Nothing);
+                return true;
+            }
+            self.history.push(Change::Odd {
+                root: rx,
+                old: self.odd[rx],
+            });
+            self.odd[rx] = true;
+            self.odd_count += 1;
+            return false;
+        }
+        if self.par[rx] > self.par[ry] {
+            std::mem::swap(&mut rx, &mut ry);
+        }
+        self.history.push(Change::Merge {
+            root: rx,
+            child: ry,
+            child_par: self.par[ry],
+            root_odd: self.odd[rx],
+        });
+        self.par[rx] += self.par[ry];
+        self.par[ry] = rx as i32;
+        self.parity[ry] = px ^ py ^ diff;
+        self.odd[rx] |= self.odd[ry];
+        self.group_count -= 1;
+        true
+    }
+    pub fn undo(&mut self) -> bool {
+        //! 最後のuniteを取り消します。履歴がなければfalse
+        match self.history.pop() {
+            Some(Change::Merge {
+                root,
+                child,
+                child_par,
+                root_odd,
+            }) => {
+                self.par[child] = child_par;
+                self.par[root] -= child_par;
+                self.parity[child] = false;
+                self.odd[root] = root_odd;
+                self.group_count += 1;
+            }
+            Some(Change::Odd { root, old }) => {
+                self.odd[root] = old;
+                self.odd_count -= 1;
+            }
+            Some(Change::Nothing) => {}
+            None => return false,
+        }
+        true
+    }
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+    pub fn rollback(&mut self, snapshot: usize) {
+        assert!(snapshot <= self.history.len());
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+    pub fn is_same_group(&self, x: usize, y: usize) -> bool {
+        self.find_root(x).0 == self.find_root(y).0
+    }
+    pub fn diff(&self, x: usize, y: usize) -> Option<bool> {
+        //! xとyの偶奇の差。違う成分ならNone
+        let (rx, px) = self.find_root(x);
+        let (ry, py) = self.find_root(y);
+        if rx == ry {
+            Some(px ^ py)
+        } else {
+            None
+        }
+    }
+    pub fn is_bipartite_group(&self, x: usize) -> bool {
+        //! xの成分に矛盾がないか
+        !self.odd[self.find_root(x).0]
+    }
+    pub fn is_bipartite(&self) -> bool {
+        //! 今までに矛盾した条件がないか
+        self.odd_count == 0
+    }
+    pub fn get_group_size(&self, x: usize) -> usize {
+        (-self.par[self.find_root(x).0]) as usize
+    }
+    pub fn get_num_of_groups(&self) -> usize {
+        self.group_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xorshift::Xorshift256;
+    // 辺の列から素朴に作った成分番号と、全域森での色と、成分ごとに矛盾がないか
+    // 全域森はuniteで実際につないだ辺(3つ目がtrue)でできています。
+    fn naive(n: usize, edges: &[(usize, usize, bool)]) -> (Vec<usize>, Vec<bool>, Vec<bool>) {
+        let mut adj = vec![vec![]; n];
+        for &(u, v, merged) in edges {
+            if merged {
+                adj[u].push(v);
+                adj[v].push(u);
+            }
+        }
+        let mut comp = vec![!0; n];
+        let mut color = vec![false; n];
+        let mut num = 0;
+        for s in 0..n {
+            if comp[s] != !0 {
+                continue;
+            }
+            comp[s] = num;
+            let mut stack = vec![s];
+            while let Some(v) = stack.pop() {
+                for &u in &adj[v] {
+                    if comp[u] == !0 {
+                        comp[u] = num;
+                        color[u] = !color[v];
+                        stack.push(u);
+                    }
+                }
+            }
+            num += 1;
+        }
+        let mut ok = vec![true; num];
+        for &(u, v, _) in edges {
+            if color[u] == color[v] {
+                ok[comp[u]] = false;
+            }
+        }
+        let bipartite = (0..n).map(|v| ok[comp[v]]).collect();
+        (comp, color, bipartite)
+    }
+    #[test]
+    fn rollback_unionfind_test() {
+        let mut rng = Xorshift256::new();
+        let n = 12;
+        let mut uf = RollbackUnionfind::new(n);
+        let mut puf = RollbackParityUnionfind::new(n);
+        let mut edges = vec![];
+        let mut snapshots = vec![];
+        for _ in 0..2000 {
+            match rng.gen() % 5 {
+                0 | 1 => {
+                    let (u, v) = (rng.gen() as usize % n, rng.gen() as usize % n);
+                    let merged = uf.unite(u, v).is_some();
+                    puf.unite(u, v, true);
+                    edges.push((u, v, merged));
+                }
+                2 => {
+                    snapshots.push((uf.snapshot(), puf.snapshot(), edges.len()));
+                }
+                3 => {
+                    if let Some((s, ps, len)) = snapshots.pop() {
+                        uf.rollback(s);
+                        puf.rollback(ps);
+                        edges.truncate(len);
+                    }
+                }
+                _ => {
+                    if uf.undo() {
+                        assert!(puf.undo());
+                        edges.pop();
+                        // undoした先のsnapshotは使えなくなります。
+                        while let Some(&(s, _, _)) = snapshots.last() {
+                            if s <= uf.snapshot() {
+                                break;
+                            }
+                            snapshots.pop();
+                        }
+                    }
+                }
+            }
+            let (comp, color, bipartite) = naive(n, &edges);
+            let groups = comp.iter().max().map_or(0, |&x| x + 1);
+            assert_eq!(uf.get_num_of_groups(), groups);
+            assert_eq!(puf.get_num_of_groups(), groups);
+            assert_eq!(puf.is_bipartite(), bipartite.iter().all(|&b| b));
+            for u in 0..n {
+                let size = comp.iter().filter(|&&c| c == comp[u]).count();
+                assert_eq!(uf.get_group_size(u), size);
+                assert_eq!(puf.get_group_size(u), size);
+                assert_eq!(puf.is_bipartite_group(u), bipartite[u]);
+                let v = rng.gen() as usize % n;
+                assert_eq!(uf.is_same_group(u, v), comp[u] == comp[v]);
+                let expected = if comp[u] == comp[v] {
+                    Some(color[u] ^ color[v])
+                } else {
+                    None
+                };
+                assert_eq!(puf.diff(u, v), expected);
+            }
+        }
+    }
+}